@@ -54,7 +54,7 @@ fn list_ops(c: &mut Criterion) {
     {
         for key_size in KEY_SIZES {
             for val_size in VAL_SIZES {
-                let mut group = c.benchmark_group(&format!(
+                let mut group = c.benchmark_group(format!(
                     "list ops, {} threads, key size: {}, val size: {}",
                     num_threads, key_size, val_size
                 ));
@@ -139,7 +139,7 @@ fn list_ops(c: &mut Criterion) {
                         for key in &keys {
                             for _ in 0..iters {
                                 store
-                                    .list_push_back(&key, val.clone())
+                                    .list_push_back(key, val.clone())
                                     .expect("failed to push");
                             }
                         }
@@ -176,7 +176,7 @@ fn list_ops(c: &mut Criterion) {
                         for key in &keys {
                             for _ in 0..iters {
                                 store
-                                    .list_push_front(&key, val.clone())
+                                    .list_push_front(key, val.clone())
                                     .expect("failed to push");
                             }
                         }
@@ -213,7 +213,7 @@ fn list_ops(c: &mut Criterion) {
                         for key in &keys {
                             for _ in 0..iters {
                                 store
-                                    .list_push_front(&key, val.clone())
+                                    .list_push_front(key, val.clone())
                                     .expect("failed to push");
                             }
                         }
@@ -250,7 +250,7 @@ fn list_ops(c: &mut Criterion) {
                         for key in &keys {
                             for _ in 0..iters {
                                 store
-                                    .list_push_back(&key, val.clone())
+                                    .list_push_back(key, val.clone())
                                     .expect("failed to push");
                             }
                         }
@@ -283,5 +283,88 @@ fn list_ops(c: &mut Criterion) {
     }
 }
 
-criterion_group!(lists, list_ops);
+const BATCH_SIZES: &[usize] = &[16, 256];
+
+fn batched_list_ops(c: &mut Criterion) {
+    for batch_size in BATCH_SIZES {
+        let mut group = c.benchmark_group(format!("batched list ops, batch size: {}", batch_size));
+        group.throughput(Throughput::Elements(*batch_size as u64));
+
+        let key = vec![0u8; KEY_SIZES[0]];
+        let val: sled::IVec = vec![1u8; VAL_SIZES[0]].as_slice().into();
+        let vals = vec![val; *batch_size];
+
+        let store = TempDb::default();
+
+        group.bench_function("list push back, one at a time", |b| {
+            b.iter_custom(|iters| {
+                store.clear().expect("failed to clear store");
+
+                let start = Instant::now();
+                for _ in 0..iters {
+                    for val in &vals {
+                        store
+                            .list_push_back(&key, val.clone())
+                            .expect("failed to push");
+                    }
+                }
+                start.elapsed()
+            })
+        });
+
+        group.bench_function("list push back many", |b| {
+            b.iter_custom(|iters| {
+                store.clear().expect("failed to clear store");
+
+                let start = Instant::now();
+                for _ in 0..iters {
+                    store
+                        .list_push_back_many(&key, vals.iter().cloned())
+                        .expect("failed to push");
+                }
+                start.elapsed()
+            })
+        });
+
+        group.bench_function("list pop front, one at a time", |b| {
+            b.iter_custom(|iters| {
+                store.clear().expect("failed to clear store");
+                for _ in 0..iters {
+                    store
+                        .list_push_back_many(&key, vals.iter().cloned())
+                        .expect("failed to push");
+                }
+
+                let start = Instant::now();
+                for _ in 0..iters {
+                    for _ in 0..*batch_size {
+                        store.list_pop_front(&key).expect("failed to pop");
+                    }
+                }
+                start.elapsed()
+            })
+        });
+
+        group.bench_function("list pop front many", |b| {
+            b.iter_custom(|iters| {
+                store.clear().expect("failed to clear store");
+                for _ in 0..iters {
+                    store
+                        .list_push_back_many(&key, vals.iter().cloned())
+                        .expect("failed to push");
+                }
+
+                let start = Instant::now();
+                for _ in 0..iters {
+                    store
+                        .list_pop_front_many(&key, *batch_size as u64)
+                        .expect("failed to pop");
+                }
+                start.elapsed()
+            })
+        });
+    }
+}
+
+criterion_group!(lists, list_ops, batched_list_ops);
 criterion_main!(lists);
@@ -2,7 +2,13 @@ use super::*;
 
 impl Conn {
     pub fn blob_get(&self, name: &[u8]) -> Result<Option<IVec>, Error> {
-        self.get_record(&keys::blob(name))?
+        let key = keys::blob(name);
+
+        if self.is_expired(&key)? {
+            return Ok(None);
+        }
+
+        self.get_record(&key)?
             .map(|rec| {
                 if rec.tag() != Tag::Blob {
                     Err(Error::BadType(Tag::Blob, rec.tag()))?
@@ -18,18 +24,20 @@ impl Conn {
         let lock = self.locks.lock(&key);
         let _guard = lock.write();
 
+        self.reap_if_expired(&key)?;
+
         let old_record = if cfg!(feature = "safe") {
             let mut batch = sled::Batch::default();
             let old_record = self.raw_remove_item(&key, &mut batch)?;
 
-            let ttl_batch = batch.clone();
             batch.insert(&key, Record::FromData(Tag::Blob, val).into_raw());
 
             self.items.apply_batch(batch)?;
-            self.ttl.apply_batch(ttl_batch)?;
+            self.clear_deadlines(&key)?;
             old_record
         } else {
             let old_rec = self.get_record(&key)?;
+            self.clear_deadlines(&key)?;
 
             match old_rec.as_ref().map(Record::tag) {
                 None | Some(Tag::Blob) => {}
@@ -37,11 +45,12 @@ impl Conn {
                     for entry in self.items.scan_prefix(&key) {
                         let (key, _) = entry?;
                         self.items.remove(&key)?;
-                        self.ttl.remove(&key)?;
                     }
                 }
             }
 
+            self.items
+                .insert(&key, Record::FromData(Tag::Blob, val).into_raw())?;
             old_rec
         };
 
@@ -0,0 +1,95 @@
+use super::*;
+use sled::transaction::{ConflictableTransactionError, TransactionError};
+
+/// The name of the database opened by [`Conn::open`] and [`Conn::with_config`].
+pub const DEFAULT: &[u8] = b"";
+
+// maps database names to the slot whose trees hold their contents, a name without an entry
+// uses the slot of the same name
+const REGISTRY: &str = "databases";
+
+// the default slot keeps the unprefixed tree names it has always used,
+// other slots prefix them with their terminated name so they can't collide
+fn tree_name(slot: &[u8], tree: &str) -> Vec<u8> {
+    if slot == DEFAULT {
+        tree.into()
+    } else {
+        let mut out = keys::bare(slot);
+        out.extend_from_slice(tree.as_bytes());
+        out
+    }
+}
+
+fn slot_of(db: &sled::Db, name: &[u8]) -> Result<IVec, sled::Error> {
+    let registry = db.open_tree(REGISTRY)?;
+    Ok(registry.get(name)?.unwrap_or_else(|| name.into()))
+}
+
+impl Conn {
+    pub(crate) fn with_db(
+        db: sled::Db,
+        name: &[u8],
+        locks: Arc<lock_table::Table>,
+        notifier: Arc<notify::Notifier>,
+    ) -> Result<Self, sled::Error> {
+        let slot = slot_of(&db, name)?;
+        let items = db.open_tree(tree_name(&slot, "items"))?;
+        let ttl = db.open_tree(tree_name(&slot, "ttl"))?;
+        let deadlines = db.open_tree(tree_name(&slot, "deadlines"))?;
+        Ok(Conn {
+            db,
+            items,
+            ttl,
+            deadlines,
+            locks,
+            notifier,
+        })
+    }
+
+    /// Opens the logical database `name` in the same sled file, creating it if needed.
+    ///
+    /// Numbered databases are just names, e.g. `b"1"`. [`DEFAULT`] selects the default database.
+    pub fn select(&self, name: &[u8]) -> Result<Self, sled::Error> {
+        // every database shares one lock table and notifier, so connections to the same
+        // database always agree, at the cost of spurious contention and wakeups on equal keys
+        Self::with_db(
+            self.db.clone(),
+            name,
+            self.locks.clone(),
+            self.notifier.clone(),
+        )
+    }
+
+    /// Exchanges the contents of databases `a` and `b`, by swapping which storage each name
+    /// refers to in a single atomic write. No entries are copied.
+    ///
+    /// Connections are bound to the contents they selected, so existing connections to `a`
+    /// keep seeing what is now `b`, and writes in flight finish there. Select `a` again to see
+    /// its new contents.
+    pub fn swap_db(&self, a: &[u8], b: &[u8]) -> Result<(), sled::Error> {
+        if a == b {
+            return Ok(());
+        }
+
+        let registry = self.db.open_tree(REGISTRY)?;
+        registry
+            .transaction(|tx| {
+                let a_slot = tx.get(a)?.unwrap_or_else(|| a.into());
+                let b_slot = tx.get(b)?.unwrap_or_else(|| b.into());
+
+                for (name, slot) in [(a, b_slot), (b, a_slot)].iter() {
+                    if *name == slot.as_ref() {
+                        tx.remove(*name)?;
+                    } else {
+                        tx.insert(*name, slot)?;
+                    }
+                }
+
+                Ok::<_, ConflictableTransactionError<()>>(())
+            })
+            .map_err(|e| match e {
+                TransactionError::Storage(e) => e,
+                TransactionError::Abort(()) => unreachable!("swap never aborts"),
+            })
+    }
+}
@@ -8,9 +8,13 @@ pub enum Error {
     #[error(transparent)]
     Table(#[from] crate::table::TableError),
     #[error(transparent)]
+    Queue(#[from] crate::queue::QueueError),
+    #[error(transparent)]
     Store(#[from] sled::Error),
     #[error(transparent)]
     Record(#[from] crate::record::RecordError),
     #[error("bad type: expected {0:?}, found {1:?}")]
     BadType(Tag, Tag),
+    #[error("scan count can't be zero")]
+    ZeroCount,
 }
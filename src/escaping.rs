@@ -61,7 +61,7 @@ impl EscapedVec {
         out
     }
 
-    pub fn as_arr(&self) -> EscapedArr {
+    pub fn as_arr(&self) -> EscapedArr<'_> {
         EscapedArr(self.0.as_ref())
     }
 
@@ -164,7 +164,7 @@ pub fn find_terminator(input: &[u8]) -> Result<usize, InvalidStrings> {
     Err(InvalidStrings::NoTerminator)
 }
 
-pub fn take_until_terminator(input: &[u8]) -> Result<(EscapedArr, &[u8]), InvalidStrings> {
+pub fn take_until_terminator(input: &[u8]) -> Result<(EscapedArr<'_>, &[u8]), InvalidStrings> {
     let found_ix = find_terminator(input)?;
 
     let first = &input[..found_ix];
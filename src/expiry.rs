@@ -0,0 +1,270 @@
+use super::*;
+use std::{
+    convert::TryFrom,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+pub const DEADLINE_BYTES: usize = 8;
+
+// deadlines are stored as big-endian milliseconds since the unix epoch
+pub fn encode_deadline(millis: u64) -> [u8; DEADLINE_BYTES] {
+    millis.to_be_bytes()
+}
+
+pub fn decode_deadline(inp: &[u8]) -> Option<u64> {
+    if inp.len() != DEADLINE_BYTES {
+        return None;
+    }
+
+    let mut buf = [0u8; DEADLINE_BYTES];
+    buf.copy_from_slice(inp);
+
+    Some(u64::from_be_bytes(buf))
+}
+
+// keys in the `deadlines` tree are the deadline followed by the raw key it applies to,
+// so iterating the tree visits keys in the order they expire
+pub fn deadline_key(deadline: u64, raw_key: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(DEADLINE_BYTES + raw_key.len());
+    out.extend_from_slice(&encode_deadline(deadline));
+    out.extend_from_slice(raw_key);
+    out
+}
+
+pub(crate) fn to_millis(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
+
+pub(crate) fn now_millis() -> u64 {
+    to_millis(SystemTime::now())
+}
+
+/// The remaining time to live of a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ttl {
+    /// The key doesn't exist, `-2` in redis.
+    Missing,
+    /// The key exists and has no expiry, `-1` in redis.
+    Persistent,
+    /// The key expires after this long, in the unit that was asked for.
+    Expires(u64),
+}
+
+impl Conn {
+    pub(crate) fn raw_deadline(&self, raw_key: &[u8]) -> Result<Option<u64>, Error> {
+        Ok(self.ttl.get(raw_key)?.and_then(|iv| decode_deadline(&iv)))
+    }
+
+    pub(crate) fn is_expired(&self, raw_key: &[u8]) -> Result<bool, Error> {
+        Ok(self
+            .raw_deadline(raw_key)?
+            .is_some_and(|deadline| deadline <= now_millis()))
+    }
+
+    pub(crate) fn set_deadline(&self, raw_key: &[u8], deadline: u64) -> Result<(), Error> {
+        self.deadlines
+            .insert(deadline_key(deadline, raw_key), &[])?;
+
+        let old = self.ttl.insert(raw_key, &encode_deadline(deadline))?;
+        if let Some(old) = old.and_then(|iv| decode_deadline(&iv)) {
+            if old != deadline {
+                self.deadlines.remove(deadline_key(old, raw_key))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn clear_deadline(&self, raw_key: &[u8]) -> Result<bool, Error> {
+        if let Some(old) = self.ttl.remove(raw_key)? {
+            if let Some(old) = decode_deadline(&old) {
+                self.deadlines.remove(deadline_key(old, raw_key))?;
+            }
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    // removes every deadline set on `prefix` or on a key under it
+    pub(crate) fn clear_deadlines(&self, prefix: &[u8]) -> Result<(), Error> {
+        let mut ttl_batch = sled::Batch::default();
+        let mut deadlines_batch = sled::Batch::default();
+
+        for entry in self.ttl.scan_prefix(prefix) {
+            let (key, deadline) = entry?;
+            if let Some(deadline) = decode_deadline(&deadline) {
+                deadlines_batch.remove(deadline_key(deadline, &key));
+            }
+            ttl_batch.remove(key);
+        }
+
+        self.ttl.apply_batch(ttl_batch)?;
+        self.deadlines.apply_batch(deadlines_batch)?;
+        Ok(())
+    }
+
+    // copies every deadline set on `src` or on a key under it to the same key under `dst`,
+    // removing the originals if `move_src` is set
+    pub(crate) fn copy_deadlines(
+        &self,
+        src: &[u8],
+        dst: &[u8],
+        move_src: bool,
+    ) -> Result<(), Error> {
+        let mut ttl_batch = sled::Batch::default();
+        let mut deadlines_batch = sled::Batch::default();
+
+        for entry in self.ttl.scan_prefix(src) {
+            let (key, deadline) = entry?;
+            let new_key = keys::rebase(src, dst, &key);
+
+            if let Some(deadline) = decode_deadline(&deadline) {
+                if move_src {
+                    deadlines_batch.remove(deadline_key(deadline, &key));
+                }
+                deadlines_batch.insert(deadline_key(deadline, &new_key), &[]);
+            }
+
+            if move_src {
+                ttl_batch.remove(key);
+            }
+            ttl_batch.insert(new_key, deadline);
+        }
+
+        self.ttl.apply_batch(ttl_batch)?;
+        self.deadlines.apply_batch(deadlines_batch)?;
+        Ok(())
+    }
+
+    // removes `raw_key` and everything under it if its deadline has passed,
+    // the caller must hold the write lock for `raw_key`
+    pub(crate) fn reap_if_expired(&self, raw_key: &[u8]) -> Result<bool, Error> {
+        if !self.is_expired(raw_key)? {
+            return Ok(false);
+        }
+
+        self.purge_item(raw_key)?;
+        Ok(true)
+    }
+
+    /// Sets `name` to expire after `timeout`.
+    ///
+    /// Returns `false` if `name` does not exist.
+    pub fn expire(&self, name: &[u8], timeout: Duration) -> Result<bool, Error> {
+        // timeouts too long to represent never come due, rather than overflowing
+        let timeout = u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX);
+        self.expire_at_millis(name, now_millis().saturating_add(timeout))
+    }
+
+    /// Sets `name` to expire at `deadline`. A deadline in the past removes the key immediately.
+    ///
+    /// Returns `false` if `name` does not exist.
+    pub fn expire_at(&self, name: &[u8], deadline: SystemTime) -> Result<bool, Error> {
+        self.expire_at_millis(name, to_millis(deadline))
+    }
+
+    fn expire_at_millis(&self, name: &[u8], deadline: u64) -> Result<bool, Error> {
+        let key = keys::bare(name).into();
+        let lock = self.locks.lock(&key);
+        let _guard = lock.write();
+
+        self.reap_if_expired(&key)?;
+
+        if self.get_record(&key)?.is_none() {
+            return Ok(false);
+        }
+
+        if deadline <= now_millis() {
+            self.purge_item(&key)?;
+        } else {
+            self.set_deadline(&key, deadline)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Remaining time to live of `name` in milliseconds.
+    ///
+    /// Tells a missing key apart from one that exists without an expiry.
+    pub fn pttl(&self, name: &[u8]) -> Result<Ttl, Error> {
+        let key = keys::bare(name);
+        let deadline = self.raw_deadline(&key)?;
+
+        let now = now_millis();
+        if deadline.is_some_and(|deadline| deadline <= now) || self.get_record(&key)?.is_none() {
+            return Ok(Ttl::Missing);
+        }
+
+        Ok(match deadline {
+            Some(deadline) => Ttl::Expires(deadline - now),
+            None => Ttl::Persistent,
+        })
+    }
+
+    /// Remaining time to live of `name` in seconds, rounded to the nearest second.
+    ///
+    /// Tells a missing key apart from one that exists without an expiry.
+    pub fn ttl(&self, name: &[u8]) -> Result<Ttl, Error> {
+        Ok(match self.pttl(name)? {
+            Ttl::Expires(ms) => Ttl::Expires((ms + 500) / 1000),
+            ttl => ttl,
+        })
+    }
+
+    /// Removes any expiry from `name`.
+    ///
+    /// Returns `true` if an expiry was removed.
+    pub fn persist(&self, name: &[u8]) -> Result<bool, Error> {
+        let key = keys::bare(name).into();
+        let lock = self.locks.lock(&key);
+        let _guard = lock.write();
+
+        if self.reap_if_expired(&key)? {
+            return Ok(false);
+        }
+
+        self.clear_deadline(&key)
+    }
+
+    /// Removes keys whose deadline has passed, in deadline order, looking at no more than
+    /// `budget` entries of the deadline index.
+    ///
+    /// Returns the number of keys removed.
+    pub fn reap_expired(&self, budget: usize) -> Result<usize, Error> {
+        let due = encode_deadline(now_millis().saturating_add(1));
+        let mut reaped = 0;
+
+        for entry in self.deadlines.range(..due).take(budget) {
+            let (ix_key, _) = entry?;
+            let deadline = decode_deadline(&ix_key[..DEADLINE_BYTES]);
+            let raw_key = &ix_key[DEADLINE_BYTES..];
+
+            // table fields are reaped under the lock of the table they belong to
+            let top_key = match find_terminator(raw_key) {
+                Ok(ix) => IVec::from(&raw_key[..ix + TERMINATOR.len()]),
+                Err(_) => {
+                    self.deadlines.remove(&ix_key)?;
+                    continue;
+                }
+            };
+
+            let lock = self.locks.lock(&top_key);
+            let _guard = lock.write();
+
+            if self.reap_if_expired(&top_key)? {
+                reaped += 1;
+            } else if top_key != raw_key {
+                reaped += self.table_reap_fields(&top_key)?;
+            }
+
+            // the entry is stale if the key was removed or its deadline changed
+            if self.raw_deadline(raw_key)? != deadline {
+                self.deadlines.remove(&ix_key)?;
+            }
+        }
+
+        Ok(reaped)
+    }
+}
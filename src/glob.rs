@@ -0,0 +1,157 @@
+// redis-style glob patterns:
+// `*` matches any sequence, `?` matches any single byte,
+// `[abc]`, `[a-z]` and `[^abc]` match byte classes, and `\` escapes the next byte
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    Literal(u8),
+    Any,
+    Star,
+    Class {
+        negated: bool,
+        ranges: Vec<(u8, u8)>,
+    },
+}
+
+impl Token {
+    fn matches(&self, byte: u8) -> bool {
+        match self {
+            Token::Literal(lit) => *lit == byte,
+            Token::Any | Token::Star => true,
+            Token::Class { negated, ranges } => {
+                ranges.iter().any(|(lo, hi)| *lo <= byte && byte <= *hi) != *negated
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pattern {
+    tokens: Vec<Token>,
+}
+
+// parses the body of a class, `inp` starts just after the opening bracket
+fn parse_class(inp: &[u8]) -> Option<(Token, usize)> {
+    let mut i = 0;
+    let negated = inp.first() == Some(&b'^');
+    if negated {
+        i += 1;
+    }
+
+    let mut ranges = Vec::new();
+    loop {
+        let lo = match inp.get(i)? {
+            b']' => return Some((Token::Class { negated, ranges }, i + 1)),
+            b'\\' => {
+                i += 1;
+                *inp.get(i)?
+            }
+            byt => *byt,
+        };
+        i += 1;
+
+        if inp.get(i) == Some(&b'-') && inp.get(i + 1).is_some_and(|b| *b != b']') {
+            let mut hi = inp[i + 1];
+            i += 2;
+            if hi == b'\\' {
+                hi = *inp.get(i)?;
+                i += 1;
+            }
+            ranges.push((lo.min(hi), lo.max(hi)));
+        } else {
+            ranges.push((lo, lo));
+        }
+    }
+}
+
+impl Pattern {
+    pub fn new(pattern: &[u8]) -> Self {
+        let mut tokens = Vec::with_capacity(pattern.len());
+        let mut i = 0;
+
+        while i < pattern.len() {
+            match pattern[i] {
+                b'*' => {
+                    // runs of stars are equivalent to a single star
+                    if tokens.last() != Some(&Token::Star) {
+                        tokens.push(Token::Star);
+                    }
+                    i += 1;
+                }
+                b'?' => {
+                    tokens.push(Token::Any);
+                    i += 1;
+                }
+                b'\\' if i + 1 < pattern.len() => {
+                    tokens.push(Token::Literal(pattern[i + 1]));
+                    i += 2;
+                }
+                // an unterminated class is matched literally
+                b'[' => match parse_class(&pattern[i + 1..]) {
+                    Some((class, used)) => {
+                        tokens.push(class);
+                        i += used + 1;
+                    }
+                    None => {
+                        tokens.push(Token::Literal(b'['));
+                        i += 1;
+                    }
+                },
+                byt => {
+                    tokens.push(Token::Literal(byt));
+                    i += 1;
+                }
+            }
+        }
+
+        Pattern { tokens }
+    }
+
+    /// The bytes every match must start with.
+    pub fn literal_prefix(&self) -> Vec<u8> {
+        self.tokens
+            .iter()
+            .map_while(|tok| match tok {
+                Token::Literal(lit) => Some(*lit),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn matches(&self, input: &[u8]) -> bool {
+        let (mut t, mut i) = (0, 0);
+        // where to resume after the last star if the current attempt fails
+        let mut backtrack: Option<(usize, usize)> = None;
+
+        while i < input.len() {
+            match self.tokens.get(t) {
+                Some(Token::Star) => {
+                    backtrack = Some((t, i));
+                    t += 1;
+                    continue;
+                }
+                Some(tok) if tok.matches(input[i]) => {
+                    t += 1;
+                    i += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            match backtrack {
+                Some((star, from)) => {
+                    t = star + 1;
+                    i = from + 1;
+                    backtrack = Some((star, from + 1));
+                }
+                None => return false,
+            }
+        }
+
+        self.tokens[t..].iter().all(|tok| *tok == Token::Star)
+    }
+}
+
+pub fn matches(pattern: &[u8], input: &[u8]) -> bool {
+    Pattern::new(pattern).matches(input)
+}
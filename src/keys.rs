@@ -4,7 +4,7 @@ pub type ListIndex = i128;
 pub const INDEX_BYTES: usize = 16;
 
 pub fn encode_list_index(i: ListIndex) -> [u8; INDEX_BYTES] {
-    (i ^ ListIndex::min_value()).to_be_bytes()
+    (i ^ ListIndex::MIN).to_be_bytes()
 }
 
 pub fn decode_list_index(inp: &[u8]) -> Option<ListIndex> {
@@ -15,7 +15,7 @@ pub fn decode_list_index(inp: &[u8]) -> Option<ListIndex> {
     let mut buf = [0u8; INDEX_BYTES];
     buf.copy_from_slice(inp);
 
-    Some(ListIndex::min_value() ^ ListIndex::from_be_bytes(buf))
+    Some(ListIndex::MIN ^ ListIndex::from_be_bytes(buf))
 }
 
 pub fn bare(name: &[u8]) -> Vec<u8> {
@@ -25,6 +25,18 @@ pub fn bare(name: &[u8]) -> Vec<u8> {
     out
 }
 
+// moves `key`, which must be `src` or a key under it, to the same position under `dst`,
+// where `src` and `dst` are both bare keys
+pub(crate) fn rebase(src: &[u8], dst: &[u8], key: &[u8]) -> Vec<u8> {
+    debug_assert!(key.starts_with(src));
+
+    let suffix = &key[src.len()..];
+    let mut out = Vec::with_capacity(dst.len() + suffix.len());
+    out.extend_from_slice(dst);
+    out.extend_from_slice(suffix);
+    out
+}
+
 pub fn blob(name: &[u8]) -> Vec<u8> {
     bare(name)
 }
@@ -80,3 +92,70 @@ pub fn table(name: &[u8], key: &[u8]) -> Vec<u8> {
 pub fn table_meta(name: &[u8]) -> Vec<u8> {
     table_inner(name, None)
 }
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Element {
+    /// A blob, or the metadata of a list or table.
+    Bare,
+    List(ListIndex),
+    Table(Vec<u8>),
+    /// A suffix that decodes both as a list index and as a table field,
+    /// use [`parse_tagged`] with the tag of the stored record to tell them apart.
+    Ambiguous(ListIndex, Vec<u8>),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParsedKey {
+    /// The unescaped name of the blob, list or table the key belongs to.
+    pub name: Vec<u8>,
+    pub element: Element,
+}
+
+fn parse_field(suffix: &[u8]) -> Option<Vec<u8>> {
+    match take_until_terminator(suffix) {
+        Ok((field, [])) => Some(field.to_vec().unescape()),
+        _ => None,
+    }
+}
+
+fn split_name(raw: &[u8]) -> Option<(Vec<u8>, &[u8])> {
+    let (name, suffix) = take_until_terminator(raw).ok()?;
+    Some((name.to_vec().unescape(), suffix))
+}
+
+/// Decodes a raw key built by this module, the inverse of [`list`], [`table`] and friends.
+///
+/// Returns `None` if `raw` isn't a valid key.
+pub fn parse(raw: &[u8]) -> Option<ParsedKey> {
+    let (name, suffix) = split_name(raw)?;
+
+    let element = if suffix.is_empty() {
+        Element::Bare
+    } else {
+        match (decode_list_index(suffix), parse_field(suffix)) {
+            (Some(ix), None) => Element::List(ix),
+            (None, Some(field)) => Element::Table(field),
+            (Some(ix), Some(field)) => Element::Ambiguous(ix, field),
+            (None, None) => return None,
+        }
+    };
+
+    Some(ParsedKey { name, element })
+}
+
+/// Like [`parse`], but uses the tag of the record stored at `raw` to resolve ambiguous keys.
+pub fn parse_tagged(raw: &[u8], tag: Tag) -> Option<ParsedKey> {
+    let (name, suffix) = split_name(raw)?;
+
+    let element = if suffix.is_empty() {
+        Element::Bare
+    } else {
+        match tag {
+            Tag::Blob => return None,
+            Tag::List => Element::List(decode_list_index(suffix)?),
+            Tag::Table => Element::Table(parse_field(suffix)?),
+        }
+    };
+
+    Some(ParsedKey { name, element })
+}
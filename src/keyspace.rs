@@ -0,0 +1,219 @@
+use super::*;
+
+// the smallest key sorting after `top` and every element key under it,
+// `top` must end with a terminator
+pub(crate) fn skip_past(top: &[u8]) -> Vec<u8> {
+    let mut out = top[..top.len() - TERMINATOR.len()].to_vec();
+    out.push(NULL + 1);
+    out
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ScanPage {
+    /// Where to resume the scan, `None` once the keyspace is exhausted.
+    pub cursor: Option<IVec>,
+    /// Unescaped key names and their types.
+    pub keys: Vec<(Vec<u8>, Tag)>,
+}
+
+impl Conn {
+    /// The type of the value stored at `name`, or `None` if there isn't one.
+    pub fn key_type(&self, name: &[u8]) -> Result<Option<Tag>, Error> {
+        let key = keys::bare(name);
+
+        if self.is_expired(&key)? {
+            return Ok(None);
+        }
+
+        Ok(self.get_record(&key)?.as_ref().map(Record::tag))
+    }
+
+    /// Counts how many of `names` exist, names given more than once are counted every time.
+    pub fn exists(&self, names: &[&[u8]]) -> Result<usize, Error> {
+        let mut found = 0;
+        for name in names {
+            if self.key_type(name)?.is_some() {
+                found += 1;
+            }
+        }
+        Ok(found)
+    }
+
+    /// Renames `src` to `dst`, along with every element and expiry, replacing anything at `dst`.
+    ///
+    /// Returns `false` if `src` does not exist.
+    pub fn rename(&self, src: &[u8], dst: &[u8]) -> Result<bool, Error> {
+        self.transfer(src, dst, true, true)
+    }
+
+    /// Like [`Conn::rename`], but leaves both keys untouched if `dst` already exists.
+    ///
+    /// Returns `false` if `src` does not exist or `dst` does.
+    pub fn rename_nx(&self, src: &[u8], dst: &[u8]) -> Result<bool, Error> {
+        self.transfer(src, dst, false, true)
+    }
+
+    /// Copies `src` to `dst`, along with every element and expiry, in a single batch.
+    /// If `replace` is unset, nothing is copied when `dst` already exists.
+    ///
+    /// Returns `false` if `src` does not exist, or if nothing was copied because of `dst`.
+    pub fn copy(&self, src: &[u8], dst: &[u8], replace: bool) -> Result<bool, Error> {
+        self.transfer(src, dst, replace, false)
+    }
+
+    fn transfer(
+        &self,
+        src: &[u8],
+        dst: &[u8],
+        replace: bool,
+        move_src: bool,
+    ) -> Result<bool, Error> {
+        let src = IVec::from(keys::bare(src));
+        let dst = IVec::from(keys::bare(dst));
+
+        let (first, second) = self.locks.lock_pair(&src, &dst);
+        let _first = first.write();
+        let _second = second.as_ref().map(|lock| lock.write());
+
+        self.reap_if_expired(&src)?;
+        self.reap_if_expired(&dst)?;
+
+        if self.get_record(&src)?.is_none() {
+            return Ok(false);
+        }
+
+        // renaming a key to itself is a no-op, copying it onto itself isn't allowed
+        if src == dst {
+            return Ok(move_src && replace);
+        }
+
+        if !replace && self.get_record(&dst)?.is_some() {
+            return Ok(false);
+        }
+
+        let mut batch = sled::Batch::default();
+        self.raw_remove_item(&dst, &mut batch)?;
+        self.raw_copy_item(&src, &dst, move_src, &mut batch)?;
+        self.items.apply_batch(batch)?;
+
+        self.clear_deadlines(&dst)?;
+        self.copy_deadlines(&src, &dst, move_src)?;
+
+        self.notifier.notify(&dst);
+        Ok(true)
+    }
+
+    // writes everything stored under `src` to the same keys under `dst` into `batch`,
+    // removing the originals if `move_src` is set
+    pub(crate) fn raw_copy_item(
+        &self,
+        src: &[u8],
+        dst: &[u8],
+        move_src: bool,
+        batch: &mut sled::Batch,
+    ) -> Result<(), Error> {
+        for entry in self.items.scan_prefix(src) {
+            let (key, val) = entry?;
+            batch.insert(keys::rebase(src, dst, &key), val);
+            if move_src {
+                batch.remove(key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists top-level keys and their types, starting at `cursor`.
+    ///
+    /// Pass `None` to start a new scan. Visits at most `count` keys and returns the live ones
+    /// along with the cursor to pass to the next call. A `count` of zero is an error, since the
+    /// scan could never move forward.
+    /// Keys created or removed during a scan may or may not be returned.
+    pub fn scan(&self, cursor: Option<&[u8]>, count: usize) -> Result<ScanPage, Error> {
+        self.scan_inner(cursor, count, &[], |_| true)
+    }
+
+    /// Like [`Conn::scan`], but only returns keys whose unescaped name matches the glob `pattern`.
+    ///
+    /// When the pattern starts with a literal prefix, only keys with that prefix are visited.
+    pub fn scan_match(
+        &self,
+        cursor: Option<&[u8]>,
+        count: usize,
+        pattern: &[u8],
+    ) -> Result<ScanPage, Error> {
+        let pattern = glob::Pattern::new(pattern);
+        let prefix = escape_optimistic(&pattern.literal_prefix());
+        self.scan_inner(cursor, count, &prefix, |name| pattern.matches(name))
+    }
+
+    /// Returns every live key whose unescaped name matches the glob `pattern`.
+    pub fn keys(&self, pattern: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+        Ok(self
+            .scan_match(None, usize::MAX, pattern)?
+            .keys
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    fn scan_inner<F: Fn(&[u8]) -> bool>(
+        &self,
+        cursor: Option<&[u8]>,
+        count: usize,
+        prefix: &[u8],
+        filter: F,
+    ) -> Result<ScanPage, Error> {
+        if count == 0 {
+            return Err(Error::ZeroCount);
+        }
+
+        let mut start = match cursor {
+            Some(cursor) if cursor > prefix => cursor.to_vec(),
+            _ => prefix.to_vec(),
+        };
+        let mut keys = Vec::new();
+        let mut visited = 0;
+
+        while visited < count {
+            let (key, val) = match self.items.range(start.as_slice()..).next() {
+                Some(entry) => entry?,
+                None => return Ok(ScanPage { cursor: None, keys }),
+            };
+
+            if !key.starts_with(prefix) {
+                return Ok(ScanPage { cursor: None, keys });
+            }
+
+            let (name, rest) = match take_until_terminator(&key) {
+                Ok(parts) => parts,
+                // not a sledis key, skip it
+                Err(_) => {
+                    start = key.to_vec();
+                    start.push(0);
+                    continue;
+                }
+            };
+
+            let top = &key[..key.len() - rest.len()];
+            start = skip_past(top);
+
+            // element keys only show up here if their collection is missing its metadata
+            if !rest.is_empty() {
+                continue;
+            }
+
+            visited += 1;
+
+            let name = name.to_vec().unescape();
+            if filter(&name) && !self.is_expired(top)? {
+                let tag = Record::decode(val)?.tag();
+                keys.push((name, tag));
+            }
+        }
+
+        Ok(ScanPage {
+            cursor: Some(start.into()),
+            keys,
+        })
+    }
+}
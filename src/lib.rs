@@ -5,12 +5,18 @@ pub mod escaping;
 use escaping::*;
 
 pub mod blob;
+pub mod databases;
+pub mod expiry;
+pub mod glob;
 pub mod keys;
+pub mod keyspace;
 pub mod list;
+pub mod queue;
 pub mod table;
 
 mod error;
 mod lock_table;
+mod notify;
 pub mod record;
 
 pub use error::*;
@@ -32,7 +38,9 @@ pub struct Conn {
     pub db: sled::Db,
     pub items: sled::Tree,
     pub ttl: sled::Tree,
+    pub deadlines: sled::Tree,
     pub locks: Arc<lock_table::Table>,
+    pub notifier: Arc<notify::Notifier>,
 }
 
 impl Conn {
@@ -42,26 +50,22 @@ impl Conn {
 
     pub fn with_config(c: &sled::Config) -> Result<Self, sled::Error> {
         let db = c.open()?;
-        let items = db.open_tree("items")?;
-        let ttl = db.open_tree("ttl")?;
         let locks = Arc::new(lock_table::Table::default());
-        Ok(Conn {
-            db,
-            items,
-            ttl,
-            locks,
-        })
+        let notifier = Arc::new(notify::Notifier::default());
+        Self::with_db(db, databases::DEFAULT, locks, notifier)
     }
 
     pub fn clear(&self) -> Result<(), sled::Error> {
         self.items.clear()?;
         self.ttl.clear()?;
+        self.deadlines.clear()?;
         Ok(())
     }
 
     pub fn flush(&self) -> Result<(), sled::Error> {
         self.items.flush()?;
         self.ttl.flush()?;
+        self.deadlines.flush()?;
         self.db.flush()?;
         Ok(())
     }
@@ -79,7 +83,7 @@ impl Conn {
         let old_rec = self.get_record(raw_key)?;
 
         match old_rec.as_ref().map(Record::tag) {
-            None | Some(Tag::Blob) => {}
+            None | Some(Tag::Blob) => batch.remove(raw_key),
             Some(Tag::List) | Some(Tag::Table) => {
                 for entry in self.items.scan_prefix(raw_key) {
                     let (key, _) = entry?;
@@ -91,22 +95,32 @@ impl Conn {
         Ok(old_rec)
     }
 
+    // the caller must hold the write lock for `raw_key`
+    pub(crate) fn purge_item(&self, raw_key: &[u8]) -> Result<Option<Record>, Error> {
+        let mut batch = sled::Batch::default();
+        let old_rec = self.raw_remove_item(raw_key, &mut batch)?;
+        self.items.apply_batch(batch)?;
+        // note: this isn't atomic bc sled transactions aren't very concurrent
+        // shouldn't be /too/ bad though, since the ttl tree will never be that large,
+        // so potentially leaking here isn't too bad
+        self.clear_deadlines(raw_key)?;
+        Ok(old_rec)
+    }
+
     pub fn remove_item(&self, key: &[u8]) -> Result<Option<Record>, Error> {
         let key = keys::bare(key).into();
         let lock = self.locks.lock(&key);
         let _guard = lock.write();
 
+        if self.reap_if_expired(&key)? {
+            return Ok(None);
+        }
+
         if cfg!(feature = "safe") {
-            let mut batch = sled::Batch::default();
-            let old_rec = self.raw_remove_item(&key, &mut batch)?;
-            self.items.apply_batch(batch.clone())?;
-            // note: this isn't atomic bc sled transactions aren't very concurrent
-            // shouldn't be /too/ bad though, since the ttl tree will never be that large,
-            // so potentially leaking here isn't too bad
-            self.ttl.apply_batch(batch)?;
-            Ok(old_rec)
+            self.purge_item(&key)
         } else {
             let old_rec = self.items.remove(&key)?.map(Record::decode).transpose()?;
+            self.clear_deadlines(&key)?;
 
             match old_rec.as_ref().map(Record::tag) {
                 None | Some(Tag::Blob) => {}
@@ -114,7 +128,6 @@ impl Conn {
                     for entry in self.items.scan_prefix(&key) {
                         let (key, _) = entry?;
                         self.items.remove(&key)?;
-                        self.ttl.remove(&key)?;
                     }
                 }
             }
@@ -0,0 +1,60 @@
+use super::*;
+use std::time::{Duration, Instant};
+
+impl Conn {
+    /// Pops the first element of the first non-empty list in `names`, waiting for an element to
+    /// be pushed if they're all empty.
+    ///
+    /// Returns the name of the list popped from along with the element, or `None` if `timeout`
+    /// passes first. A `timeout` of `None` waits forever.
+    /// Each element is only ever returned to one caller. Only writes made through this `Conn`,
+    /// its clones and the databases it selects wake waiters.
+    pub fn list_pop_front_blocking(
+        &self,
+        names: &[&[u8]],
+        timeout: Option<Duration>,
+    ) -> Result<Option<(Vec<u8>, IVec)>, Error> {
+        self.list_pop_blocking(names, timeout, Self::list_pop_front)
+    }
+
+    /// Like [`Conn::list_pop_front_blocking`], but pops from the back of the lists.
+    pub fn list_pop_back_blocking(
+        &self,
+        names: &[&[u8]],
+        timeout: Option<Duration>,
+    ) -> Result<Option<(Vec<u8>, IVec)>, Error> {
+        self.list_pop_blocking(names, timeout, Self::list_pop_back)
+    }
+
+    fn list_pop_blocking<F>(
+        &self,
+        names: &[&[u8]],
+        timeout: Option<Duration>,
+        pop: F,
+    ) -> Result<Option<(Vec<u8>, IVec)>, Error>
+    where
+        F: Fn(&Self, &[u8]) -> Result<Option<IVec>, Error>,
+    {
+        // a timeout too long to represent waits forever
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        let meta_keys = names
+            .iter()
+            .map(|name| IVec::from(keys::list_meta(name)))
+            .collect::<Vec<_>>();
+
+        loop {
+            // register before checking the lists, so a push after the check still wakes us
+            let registration = self.notifier.register(meta_keys.clone());
+
+            for name in names {
+                if let Some(val) = pop(self, name)? {
+                    return Ok(Some((name.to_vec(), val)));
+                }
+            }
+
+            if !registration.wait(deadline) {
+                return Ok(None);
+            }
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use super::*;
+use std::collections::HashSet;
+
+impl Conn {
+    /// Inserts `val` just before or after the first element equal to `pivot`.
+    ///
+    /// Returns the new length, or `None` if `pivot` wasn't found.
+    pub fn list_insert(
+        &self,
+        name: &[u8],
+        before: bool,
+        pivot: &[u8],
+        val: IVec,
+    ) -> Result<Option<u64>, Error> {
+        let meta_key = IVec::from(keys::list_meta(name));
+
+        let mutex = self.locks.lock(&meta_key);
+        let _guard = mutex.write();
+
+        self.reap_if_expired(&meta_key)?;
+
+        let mut meta = self.list_get_meta(name)?;
+        let (head, tail) = match (meta.head_ix(), meta.tail_ix()) {
+            (Some(head), Some(tail)) => (head, tail),
+            _ => return Ok(None),
+        };
+
+        let mut elems = self
+            .items
+            .range(keys::list(name, head)..=keys::list(name, tail));
+        let mut prev = None;
+
+        let found = loop {
+            let (key, val) = match elems.next() {
+                Some(entry) => entry?,
+                None => break None,
+            };
+            let ix = key_ix(&key);
+
+            if decode_item(val)? == pivot {
+                break Some(ix);
+            }
+            prev = Some(ix);
+        };
+
+        let (prev, next) = match found {
+            None => return Ok(None),
+            Some(ix) if before => (prev, Some(ix)),
+            Some(ix) => {
+                let next = elems.next().transpose()?.map(|(key, _)| key_ix(&key));
+                (Some(ix), next)
+            }
+        };
+
+        self.list_insert_between(name, &meta_key, &mut meta, prev, next, val)?;
+        Ok(Some(meta.len()))
+    }
+
+    /// Inserts `val` so it ends up at position `pos`, shifting later elements back.
+    ///
+    /// `pos` may be the length of the list, which appends `val`.
+    /// Returns the new length, or `None` if `pos` is past the end of the list.
+    pub fn list_insert_at(&self, name: &[u8], pos: u64, val: IVec) -> Result<Option<u64>, Error> {
+        let meta_key = IVec::from(keys::list_meta(name));
+
+        let mutex = self.locks.lock(&meta_key);
+        let _guard = mutex.write();
+
+        self.reap_if_expired(&meta_key)?;
+
+        let mut meta = self.list_get_meta(name)?;
+        if pos > meta.len() {
+            return Ok(None);
+        }
+
+        let prev = if pos == 0 {
+            None
+        } else {
+            self.list_ix(name, &meta, pos as i64 - 1)?
+        };
+        let next = if pos == meta.len() {
+            None
+        } else {
+            self.list_ix(name, &meta, pos as i64)?
+        };
+
+        self.list_insert_between(name, &meta_key, &mut meta, prev, next, val)?;
+        Ok(Some(meta.len()))
+    }
+
+    // inserts `val` between the adjacent elements at `prev` and `next`, where `None` is past
+    // the end of the list. when there's no index left between them, a window of neighbouring
+    // elements is spread out to make room, growing until it has enough space.
+    //
+    // a full capped list evicts from the back to insert at the front, and from the front
+    // otherwise. if that evicts `prev`, the new element becomes the head.
+    fn list_insert_between(
+        &self,
+        name: &[u8],
+        meta_key: &IVec,
+        meta: &mut Meta,
+        mut prev: Option<ListIndex>,
+        next: Option<ListIndex>,
+        val: IVec,
+    ) -> Result<(), Error> {
+        let evicted = self.list_make_room(name, meta, prev.is_none())?;
+        if let Some(ix) = prev {
+            if meta.head_ix().is_none_or(|head| ix < head) {
+                prev = None;
+            }
+        }
+
+        let ix = match (prev, next) {
+            (None, _) => meta.push_front(),
+            (_, None) => meta.push_back(),
+            (Some(prev), Some(next)) if next - prev >= 2 => {
+                meta.insert_between();
+                prev + (next - prev) / 2
+            }
+            (Some(_), Some(next)) => {
+                return self.list_spread_insert(name, meta_key, meta, next, val, evicted)
+            }
+        };
+
+        let mut batch = sled::Batch::default();
+        // evicted first, the new element may reuse an evicted key
+        for key in evicted {
+            batch.remove(key);
+        }
+        batch.insert(
+            keys::list(name, ix),
+            Record::FromData(Tag::List, val).into_raw(),
+        );
+        batch.insert(meta_key, meta.encode().into_raw());
+        self.items.apply_batch(batch)?;
+
+        self.notifier.notify(meta_key);
+        Ok(())
+    }
+
+    // inserts `val` just before `next` when there's no free index there, by re-indexing the
+    // elements around it evenly. the window doubles until the indices between its outside
+    // neighbours leave a gap of at least two, a window reaching an end of the list can always
+    // spread out past it.
+    fn list_spread_insert(
+        &self,
+        name: &[u8],
+        meta_key: &IVec,
+        meta: &mut Meta,
+        next: ListIndex,
+        val: IVec,
+        evicted: Vec<Vec<u8>>,
+    ) -> Result<(), Error> {
+        let (head, tail) = match (meta.head_ix(), meta.tail_ix()) {
+            (Some(head), Some(tail)) => (head, tail),
+            _ => return Err(ListError::MissingVal(name.to_vec(), next).into()),
+        };
+        let next_key = keys::list(name, next);
+        let spacing = DEFAULT_STRIDE as ListIndex;
+        let mut width = 1;
+
+        loop {
+            let before = self
+                .list_iter_back(name, head, next)
+                .take(width + 1)
+                .collect::<Result<Vec<_>, _>>()?;
+            let after = self
+                .items
+                .range(next_key.clone()..=keys::list(name, tail))
+                .take(width + 1)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let lo = before.get(width).map(|(key, _)| key_ix(key));
+            let hi = after.get(width).map(|(key, _)| key_ix(key));
+
+            // the window's current keys and raw records in order, `None` for the new element
+            let window = before
+                .into_iter()
+                .take(width)
+                .rev()
+                .map(|(key, raw)| (Some(key), raw))
+                .chain(std::iter::once((
+                    None,
+                    Record::FromData(Tag::List, val.clone()).into_raw(),
+                )))
+                .chain(
+                    after
+                        .into_iter()
+                        .take(width)
+                        .map(|(key, raw)| (Some(key), raw)),
+                )
+                .collect::<Vec<_>>();
+
+            let count = window.len() as ListIndex;
+            let (start, step) = match (lo, hi) {
+                (Some(lo), Some(hi)) => (lo, (hi - lo) / (count + 1)),
+                (Some(lo), None) => (lo, spacing),
+                (None, Some(hi)) => (hi - spacing * (count + 1), spacing),
+                (None, None) => (head - spacing, spacing),
+            };
+
+            if step < 2 {
+                width *= 2;
+                continue;
+            }
+
+            let mut batch = sled::Batch::default();
+            let mut new_keys = HashSet::new();
+
+            // evicted first, the window may spread over evicted keys
+            for key in &evicted {
+                batch.remove(key.as_slice());
+            }
+
+            for (i, (_, raw)) in (1..).zip(window.iter()) {
+                let new_key = keys::list(name, start + step * i);
+                batch.insert(new_key.as_slice(), raw.clone());
+                new_keys.insert(new_key);
+            }
+
+            for (key, _) in window.iter() {
+                if let Some(key) = key {
+                    if !new_keys.contains(key.as_ref()) {
+                        batch.remove(key);
+                    }
+                }
+            }
+
+            let new_head = if lo.is_none() { start + step } else { head };
+            let new_tail = if hi.is_none() {
+                start + step * count
+            } else {
+                tail
+            };
+
+            meta.insert_between();
+            meta.set_bounds(new_head, new_tail, meta.len());
+            batch.insert(meta_key, meta.encode().into_raw());
+
+            self.items.apply_batch(batch)?;
+            return Ok(());
+        }
+    }
+}
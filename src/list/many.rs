@@ -0,0 +1,137 @@
+use super::*;
+
+impl Conn {
+    /// Pushes every value in `vals` onto the front of `name` in order, so the last one ends up
+    /// first, returning the new length.
+    ///
+    /// The metadata is updated once and every element is written in a single batch.
+    pub fn list_push_front_many<I: IntoIterator<Item = IVec>>(
+        &self,
+        name: &[u8],
+        vals: I,
+    ) -> Result<u64, Error> {
+        self.list_push_many(name, vals, false)
+    }
+
+    /// Pushes every value in `vals` onto the back of `name` in order, returning the new length.
+    ///
+    /// The metadata is updated once and every element is written in a single batch.
+    pub fn list_push_back_many<I: IntoIterator<Item = IVec>>(
+        &self,
+        name: &[u8],
+        vals: I,
+    ) -> Result<u64, Error> {
+        self.list_push_many(name, vals, true)
+    }
+
+    /// Pops up to `count` elements from the front of `name`, in the order they were popped.
+    pub fn list_pop_front_many(&self, name: &[u8], count: u64) -> Result<Vec<IVec>, Error> {
+        self.list_pop_many(name, count, false)
+    }
+
+    /// Pops up to `count` elements from the back of `name`, in the order they were popped.
+    pub fn list_pop_back_many(&self, name: &[u8], count: u64) -> Result<Vec<IVec>, Error> {
+        self.list_pop_many(name, count, true)
+    }
+
+    fn list_push_many<I: IntoIterator<Item = IVec>>(
+        &self,
+        name: &[u8],
+        vals: I,
+        back: bool,
+    ) -> Result<u64, Error> {
+        let vals = vals.into_iter().collect::<Vec<_>>();
+
+        let meta_key = IVec::from(keys::list_meta(name));
+
+        let mutex = self.locks.lock(&meta_key);
+        let _guard = mutex.write();
+
+        self.reap_if_expired(&meta_key)?;
+
+        let mut meta = self.list_get_meta(name)?;
+        if vals.is_empty() {
+            return Ok(meta.len());
+        }
+
+        // a capped list only keeps the last `cap` values pushed, so evict from the existing
+        // elements up front and skip the values that would be evicted right away
+        let (evicted, skip) = match meta.cap() {
+            Some(cap) => {
+                let pushed = vals.len() as u64;
+                let keep = cap.saturating_sub(pushed);
+                let evicted = self.list_evict(name, &mut meta, !back, keep)?;
+                (evicted, pushed.saturating_sub(cap) as usize)
+            }
+            None => (Vec::new(), 0),
+        };
+
+        let mut batch = sled::Batch::default();
+        // evicted first, new elements may reuse evicted keys
+        for key in evicted {
+            batch.remove(key);
+        }
+
+        for val in vals.into_iter().skip(skip) {
+            let ix = if back {
+                meta.push_back()
+            } else {
+                meta.push_front()
+            };
+            batch.insert(
+                keys::list(name, ix),
+                Record::FromData(Tag::List, val).into_raw(),
+            );
+        }
+
+        batch.insert(&meta_key, meta.encode().into_raw());
+        self.items.apply_batch(batch)?;
+
+        self.notifier.notify(&meta_key);
+        Ok(meta.len())
+    }
+
+    fn list_pop_many(&self, name: &[u8], count: u64, back: bool) -> Result<Vec<IVec>, Error> {
+        let meta_key = IVec::from(keys::list_meta(name));
+
+        let mutex = self.locks.lock(&meta_key);
+        let _guard = mutex.write();
+
+        self.reap_if_expired(&meta_key)?;
+
+        let mut meta = self.list_get_meta(name)?;
+        let mut batch = sled::Batch::default();
+        let mut popped = Vec::new();
+
+        while (popped.len() as u64) < count {
+            let ix = match self.list_pop_ix(name, &mut meta, back)? {
+                Some(ix) => ix,
+                None => break,
+            };
+
+            let item_key = keys::list(name, ix);
+            match self.items.get(&item_key)? {
+                Some(raw) => popped.push(decode_item(raw)?),
+                None => return Err(ListError::MissingVal(name.to_vec(), ix).into()),
+            }
+            batch.remove(item_key);
+        }
+
+        if popped.is_empty() {
+            return Ok(popped);
+        }
+
+        if meta.is_empty() {
+            batch.remove(&meta_key);
+        } else {
+            batch.insert(&meta_key, meta.encode().into_raw());
+        }
+        self.items.apply_batch(batch)?;
+
+        if meta.is_empty() {
+            self.clear_deadline(&meta_key)?;
+        }
+
+        Ok(popped)
+    }
+}
@@ -1,47 +1,172 @@
 use super::*;
+use std::num::NonZeroU64;
+
+/// Distance between the indices of adjacent elements pushed onto a new list,
+/// leaving room to insert elements between them without re-indexing.
+pub const DEFAULT_STRIDE: u64 = 1 << 32;
 
 // list metadata type
-#[derive(Default, Copy, Clone, Eq, PartialEq, Debug)]
+//
+// elements live at indices from `head` to `tail` inclusive. pushes extend either end by
+// `stride`, so a list is regular (and positions map directly to indices) until an element
+// is inserted in the middle, which marks it `gapped`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct Meta {
     head: ListIndex,
     len: u64,
+    tail: ListIndex,
+    stride: u64,
+    gapped: bool,
+    // zero if the list isn't capped
+    cap: u64,
 }
 
-pub const META_SIZE: usize = INDEX_BYTES + 8;
+impl Default for Meta {
+    fn default() -> Self {
+        Self {
+            head: 0,
+            len: 0,
+            tail: 0,
+            stride: DEFAULT_STRIDE,
+            gapped: false,
+            cap: 0,
+        }
+    }
+}
+
+// metadata written before lists had gaps: a head and a length, with a stride of one
+pub const DENSE_META_SIZE: usize = INDEX_BYTES + 8;
+// metadata written before lists could be capped
+pub const UNCAPPED_META_SIZE: usize = DENSE_META_SIZE + INDEX_BYTES + 8 + 1;
+pub const META_SIZE: usize = UNCAPPED_META_SIZE + 8;
 
 impl Meta {
     pub fn encode(self) -> Record {
         let mut out = [0u8; META_SIZE];
         out[..INDEX_BYTES].copy_from_slice(&self.head.to_be_bytes());
-        out[INDEX_BYTES..].copy_from_slice(&self.len.to_be_bytes());
+        out[INDEX_BYTES..DENSE_META_SIZE].copy_from_slice(&self.len.to_be_bytes());
+        out[DENSE_META_SIZE..DENSE_META_SIZE + INDEX_BYTES]
+            .copy_from_slice(&self.tail.to_be_bytes());
+        out[DENSE_META_SIZE + INDEX_BYTES..UNCAPPED_META_SIZE - 1]
+            .copy_from_slice(&self.stride.to_be_bytes());
+        out[UNCAPPED_META_SIZE - 1] = self.gapped as u8;
+        out[UNCAPPED_META_SIZE..].copy_from_slice(&self.cap.to_be_bytes());
 
-        Record::FromData(Tag::List, (&out).into())
+        Record::FromData(Tag::List, IVec::from(&out[..]))
     }
 
     pub fn decode(inp: &Record) -> Result<Self, Error> {
         if inp.tag() != Tag::List {
-            Err(Error::BadType(Tag::Table, inp.tag()))?
-        } else if inp.len() != META_SIZE {
+            Err(Error::BadType(Tag::List, inp.tag()))?
+        } else if ![DENSE_META_SIZE, UNCAPPED_META_SIZE, META_SIZE].contains(&inp.len()) {
             Err(ListError::InvalidMeta(inp.data()))?
         } else {
             let mut head_buf = [0u8; INDEX_BYTES];
             head_buf.copy_from_slice(&inp[..INDEX_BYTES]);
             let mut len_buf = [0u8; 8];
-            len_buf.copy_from_slice(&inp[INDEX_BYTES..]);
+            len_buf.copy_from_slice(&inp[INDEX_BYTES..DENSE_META_SIZE]);
+
+            let head = ListIndex::from_be_bytes(head_buf);
+            let len = u64::from_be_bytes(len_buf);
+
+            if inp.len() == DENSE_META_SIZE {
+                return Ok(Self {
+                    head,
+                    len,
+                    tail: head + len as ListIndex - 1,
+                    stride: 1,
+                    gapped: false,
+                    cap: 0,
+                });
+            }
+
+            let mut tail_buf = [0u8; INDEX_BYTES];
+            tail_buf.copy_from_slice(&inp[DENSE_META_SIZE..DENSE_META_SIZE + INDEX_BYTES]);
+            let mut stride_buf = [0u8; 8];
+            stride_buf.copy_from_slice(&inp[DENSE_META_SIZE + INDEX_BYTES..UNCAPPED_META_SIZE - 1]);
+
+            let mut cap_buf = [0u8; 8];
+            if inp.len() == META_SIZE {
+                cap_buf.copy_from_slice(&inp[UNCAPPED_META_SIZE..]);
+            }
+
             Ok(Self {
-                head: ListIndex::from_be_bytes(head_buf),
-                len: u64::from_be_bytes(len_buf),
+                head,
+                len,
+                tail: ListIndex::from_be_bytes(tail_buf),
+                stride: u64::from_be_bytes(stride_buf),
+                gapped: inp[UNCAPPED_META_SIZE - 1] != 0,
+                cap: u64::from_be_bytes(cap_buf),
             })
         }
     }
 
-    pub fn mk_key(&self, ix: i64) -> Option<ListIndex> {
+    /// The most elements the list may hold, pushes past it evict from the opposite end.
+    pub fn cap(&self) -> Option<u64> {
+        if self.cap == 0 {
+            None
+        } else {
+            Some(self.cap)
+        }
+    }
+
+    pub(super) fn set_cap(&mut self, cap: Option<NonZeroU64>) {
+        self.cap = cap.map_or(0, NonZeroU64::get);
+    }
+
+    // the offset from the front of position `ix`, negative positions count from the back
+    pub fn offset(&self, ix: i64) -> Option<u64> {
+        if self.len == 0 {
+            return None;
+        }
+
         let offset = ix.rem_euclid(self.len as i64);
         let valid_ix = ix <= offset; // ix <= offset <-> (ix < 0 /\ ix.abs() <= self.len) \/ (ix > 0 /\ ix < self.len)
         if valid_ix {
-            Some(self.head + offset as ListIndex)
+            Some(offset as u64)
+        } else {
+            None
+        }
+    }
+
+    /// The index of position `ix`, or `None` if it's out of range or the list isn't regular.
+    pub fn mk_key(&self, ix: i64) -> Option<ListIndex> {
+        if !self.is_regular() {
+            return None;
+        }
+
+        let offset = self.offset(ix)?;
+        Some(self.head + offset as ListIndex * self.stride as ListIndex)
+    }
+
+    /// Whether every element is exactly `stride` apart from its neighbours.
+    pub fn is_regular(&self) -> bool {
+        !self.gapped || self.len <= 1
+    }
+
+    pub fn stride(&self) -> u64 {
+        self.stride
+    }
+
+    // normalizes an inclusive range of positions with redis semantics:
+    // negative positions count from the back, and out of range positions are clamped
+    pub fn range(&self, start: i64, stop: i64) -> Option<(u64, u64)> {
+        let len = self.len as i64;
+        let start = if start < 0 {
+            (len + start).max(0)
         } else {
+            start
+        };
+        let stop = if stop < 0 {
+            len + stop
+        } else {
+            stop.min(len - 1)
+        };
+
+        if start > stop || start >= len {
             None
+        } else {
+            Some((start as u64, stop as u64))
         }
     }
 
@@ -55,7 +180,7 @@ impl Meta {
 
     pub fn tail_ix(&self) -> Option<ListIndex> {
         if self.len != 0 {
-            Some(self.head + self.len as ListIndex - 1)
+            Some(self.tail)
         } else {
             None
         }
@@ -69,28 +194,83 @@ impl Meta {
         self.len() == 0
     }
 
-    pub(super) fn push_front(&mut self) -> ListIndex {
-        self.head -= 1;
+    pub(crate) fn push_front(&mut self) -> ListIndex {
+        if self.len == 0 {
+            self.tail = self.head;
+        } else {
+            self.head -= self.stride as ListIndex;
+        }
         self.len += 1;
 
         self.head
     }
 
+    // for lists that aren't regular, the caller must move the head to the next element
     pub(super) fn pop_front(&mut self) -> Option<ListIndex> {
         let res = self.head_ix()?;
-        self.head += 1;
+        self.head += self.stride as ListIndex;
         self.len -= 1;
+        self.gapped &= self.len > 1;
         Some(res)
     }
 
-    pub(super) fn push_back(&mut self) -> ListIndex {
+    pub(crate) fn push_back(&mut self) -> ListIndex {
+        if self.len == 0 {
+            self.tail = self.head;
+        } else {
+            self.tail += self.stride as ListIndex;
+        }
         self.len += 1;
-        self.head + self.len as ListIndex - 1
+
+        self.tail
     }
 
+    // for lists that aren't regular, the caller must move the tail to the previous element
     pub(super) fn pop_back(&mut self) -> Option<ListIndex> {
         let res = self.tail_ix()?;
+        self.tail -= self.stride as ListIndex;
         self.len -= 1;
+        self.gapped &= self.len > 1;
         Some(res)
     }
+
+    // moves the ends of the list, `head` and `tail` must be the indices of its first and last
+    // elements once it holds `len` of them
+    pub(super) fn set_bounds(&mut self, head: ListIndex, tail: ListIndex, len: u64) {
+        debug_assert!(len > 0 && head <= tail);
+        self.head = head;
+        self.tail = tail;
+        self.len = len;
+        self.gapped &= len > 1;
+    }
+
+    // re-indexes the list as `len` regular elements starting at `head`
+    pub(super) fn set_regular(&mut self, head: ListIndex, len: u64) {
+        debug_assert!(len > 0);
+        self.head = head;
+        self.tail = head + (len - 1) as ListIndex * self.stride as ListIndex;
+        self.len = len;
+        self.gapped = false;
+    }
+
+    // records an element inserted between two others, so positions no longer map to indices
+    pub(super) fn insert_between(&mut self) {
+        self.len += 1;
+        self.gapped = true;
+    }
+
+    // drops `removed` elements, re-indexing the survivors regularly and packed against
+    // the back if `from_back` is set and against the front otherwise
+    pub(super) fn compact(&mut self, removed: u64, from_back: bool) {
+        debug_assert!(removed < self.len);
+        self.len -= removed;
+        self.gapped = false;
+
+        let span = (self.len - 1) as ListIndex * self.stride as ListIndex;
+        if from_back {
+            self.head = self.tail - span;
+        } else {
+            self.tail = self.head + span;
+        }
+    }
 }
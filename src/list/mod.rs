@@ -1,14 +1,284 @@
 use super::*;
 use sled::IVec;
+use std::{num::NonZeroU64, ops::Bound};
 use thiserror::*;
 
 mod meta;
 pub use self::meta::*;
 
+mod range;
+pub use self::range::*;
+
+mod blocking;
+
+mod insert;
+
+mod many;
+
+mod moves;
+pub use self::moves::*;
+
+mod search;
+
+// decodes a raw list element
+pub(crate) fn decode_item(iv: IVec) -> Result<IVec, Error> {
+    let rec = Record::decode(iv)?;
+    if rec.tag() != Tag::List {
+        Err(Error::BadType(Tag::List, rec.tag()))
+    } else {
+        Ok(rec.data())
+    }
+}
+
+// the index of a raw list element key
+fn key_ix(key: &[u8]) -> ListIndex {
+    decode_list_index(&key[key.len() - INDEX_BYTES..]).expect("invalid list key")
+}
+
+// the index of the last element of `name` in `lo..hi`.
+//
+// sled's reverse iteration can loop without end when it crosses a node boundary right after
+// long runs of 0xff, which is how negative list indices encode, so this binary searches with
+// forward range probes instead. once the first probe finds an element, there is always an
+// element at `lo` and none in `hi..`, every probe halves the space between them.
+fn prev_ix(
+    items: &sled::Tree,
+    name: &[u8],
+    lo: ListIndex,
+    hi: ListIndex,
+) -> Result<Option<ListIndex>, Error> {
+    if hi <= lo {
+        return Ok(None);
+    }
+
+    let mut lo = match items
+        .range(keys::list(name, lo)..keys::list(name, hi))
+        .next()
+    {
+        Some(entry) => key_ix(&entry?.0),
+        None => return Ok(None),
+    };
+    let mut hi = hi;
+
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        match items
+            .range(keys::list(name, mid)..keys::list(name, hi))
+            .next()
+        {
+            Some(entry) => lo = key_ix(&entry?.0),
+            None => hi = mid,
+        }
+    }
+
+    Ok(Some(lo))
+}
+
 impl Conn {
+    // the index of the element at position `pos`, negative positions count from the back
+    pub(crate) fn list_ix(
+        &self,
+        name: &[u8],
+        meta: &Meta,
+        pos: i64,
+    ) -> Result<Option<ListIndex>, Error> {
+        let offset = match meta.offset(pos) {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+
+        if let Some(ix) = meta.mk_key(offset as i64) {
+            return Ok(Some(ix));
+        }
+
+        // lists with gaps are walked from the front, the ends are known
+        let (head, tail) = (meta.head_ix().unwrap(), meta.tail_ix().unwrap());
+        if offset == 0 {
+            return Ok(Some(head));
+        } else if offset == meta.len() - 1 {
+            return Ok(Some(tail));
+        }
+
+        let found = self
+            .items
+            .range(keys::list(name, head)..=keys::list(name, tail))
+            .nth(offset as usize);
+
+        match found {
+            Some(entry) => Ok(Some(key_ix(&entry?.0))),
+            None => Err(ListError::MissingVal(name.to_vec(), tail).into()),
+        }
+    }
+
+    // the index of the last element before `ix`, stopping at the element at `head`, see prev_ix
+    pub(crate) fn list_prev_ix(
+        &self,
+        name: &[u8],
+        head: ListIndex,
+        ix: ListIndex,
+    ) -> Result<Option<ListIndex>, Error> {
+        prev_ix(&self.items, name, head, ix)
+    }
+
+    // the keys and raw records of the elements before `ix` back to the one at `head`,
+    // last first, see list_prev_ix
+    pub(crate) fn list_iter_back<'a>(
+        &'a self,
+        name: &'a [u8],
+        head: ListIndex,
+        ix: ListIndex,
+    ) -> impl Iterator<Item = Result<(IVec, IVec), Error>> + 'a {
+        let mut cursor = Some(ix);
+        std::iter::from_fn(move || {
+            let prev = match self.list_prev_ix(name, head, cursor?) {
+                Ok(prev) => prev,
+                Err(e) => {
+                    cursor = None;
+                    return Some(Err(e));
+                }
+            };
+            cursor = prev;
+            let prev = prev?;
+
+            let key = keys::list(name, prev);
+            Some(match self.items.get(&key) {
+                Ok(Some(raw)) => Ok((key.into(), raw)),
+                Ok(None) => Err(ListError::MissingVal(name.to_vec(), prev).into()),
+                Err(e) => Err(e.into()),
+            })
+        })
+    }
+
+    // removes the first or last element from `meta`, returning its index,
+    // lists that aren't regular find their new end by seeking past the removed element
+    pub(crate) fn list_pop_ix(
+        &self,
+        name: &[u8],
+        meta: &mut Meta,
+        back: bool,
+    ) -> Result<Option<ListIndex>, Error> {
+        let regular = meta.is_regular();
+        let (head, tail) = match (meta.head_ix(), meta.tail_ix()) {
+            (Some(head), Some(tail)) => (head, tail),
+            _ => return Ok(None),
+        };
+
+        if back {
+            meta.pop_back();
+        } else {
+            meta.pop_front();
+        }
+
+        if !regular && !meta.is_empty() {
+            let next = if back {
+                self.list_prev_ix(name, head, tail)?
+            } else {
+                let (head_key, tail_key) = (keys::list(name, head), keys::list(name, tail));
+                match self
+                    .items
+                    .range::<&[u8], _>((
+                        Bound::Excluded(head_key.as_slice()),
+                        Bound::Included(tail_key.as_slice()),
+                    ))
+                    .next()
+                {
+                    Some(entry) => Some(key_ix(&entry?.0)),
+                    None => None,
+                }
+            };
+            let next = match next {
+                Some(ix) => ix,
+                None => return Err(ListError::MissingVal(name.to_vec(), head).into()),
+            };
+
+            if back {
+                meta.set_bounds(head, next, meta.len());
+            } else {
+                meta.set_bounds(next, tail, meta.len());
+            }
+        }
+
+        Ok(Some(if back { tail } else { head }))
+    }
+
+    // pops elements from the front or back until there's room to push one more under the cap,
+    // returning the keys to remove
+    pub(crate) fn list_make_room(
+        &self,
+        name: &[u8],
+        meta: &mut Meta,
+        back: bool,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        let keep = match meta.cap() {
+            Some(cap) => cap - 1,
+            None => return Ok(Vec::new()),
+        };
+        self.list_evict(name, meta, back, keep)
+    }
+
+    // pops elements from the front or back until at most `keep` are left,
+    // returning the keys to remove
+    pub(crate) fn list_evict(
+        &self,
+        name: &[u8],
+        meta: &mut Meta,
+        back: bool,
+        keep: u64,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        let mut evicted = Vec::new();
+        while meta.len() > keep {
+            match self.list_pop_ix(name, meta, back)? {
+                Some(ix) => evicted.push(keys::list(name, ix)),
+                None => break,
+            }
+        }
+        Ok(evicted)
+    }
+
+    /// Caps `name` at `cap` elements, or removes its cap if `cap` is `None`.
+    ///
+    /// Pushes onto a full list evict an element from the opposite end in the same batch.
+    /// If the list is already longer than `cap`, elements are evicted from the front right away.
+    /// The cap is stored with the list, so it's dropped when the list is emptied or removed,
+    /// use [`Conn::list_push_back_capped`] and friends to keep a list capped from its first push.
+    /// Inserts also respect the cap, requeued [`queue::Queue`] items don't, so they're never lost.
+    /// Returns `false` if the list doesn't exist.
+    pub fn list_set_cap(&self, name: &[u8], cap: Option<NonZeroU64>) -> Result<bool, Error> {
+        let meta_key = IVec::from(keys::list_meta(name));
+
+        let mutex = self.locks.lock(&meta_key);
+        let _guard = mutex.write();
+
+        self.reap_if_expired(&meta_key)?;
+
+        let mut meta = self.list_get_meta(name)?;
+        if meta.is_empty() {
+            return Ok(false);
+        }
+
+        meta.set_cap(cap);
+        let evicted = match cap {
+            Some(cap) => self.list_evict(name, &mut meta, false, cap.get())?,
+            None => Vec::new(),
+        };
+
+        let mut batch = sled::Batch::default();
+        for key in evicted {
+            batch.remove(key);
+        }
+        batch.insert(&meta_key, meta.encode().into_raw());
+        self.items.apply_batch(batch)?;
+
+        Ok(true)
+    }
+
     pub fn list_get_meta(&self, name: &[u8]) -> Result<Meta, Error> {
         let key = keys::list_meta(name);
 
+        if self.is_expired(&key)? {
+            return Ok(Meta::default());
+        }
+
         if let Some(bs) = self.get_record(&key)? {
             Meta::decode(&bs)
         } else {
@@ -20,6 +290,12 @@ impl Conn {
         Ok(self.list_get_meta(name)?.len())
     }
 
+    /// Reads the element at position `ix`, negative positions count from the back.
+    ///
+    /// Finding the element is a single lookup until one is inserted in the middle with
+    /// [`Conn::list_insert`] or [`Conn::list_insert_at`]. From then on positions other than the
+    /// ends are found by walking the list, linear in its length, until [`Conn::list_trim`] or
+    /// [`Conn::list_remove`] re-indexes it or it's emptied.
     pub fn list_get(&self, name: &[u8], ix: i64) -> Result<Option<IVec>, Error> {
         let meta_key = IVec::from(keys::list_meta(name));
 
@@ -28,7 +304,7 @@ impl Conn {
 
         let meta = self.list_get_meta(name)?;
 
-        if let Some(ix) = meta.mk_key(ix) {
+        if let Some(ix) = self.list_ix(name, &meta, ix)? {
             self.get_record(&keys::list(name, ix))?
                 .map(|rec| {
                     if rec.tag() != Tag::List {
@@ -44,50 +320,83 @@ impl Conn {
     }
 
     pub fn list_push_front(&self, name: &[u8], val: IVec) -> Result<(), Error> {
-        let meta_key = IVec::from(keys::list_meta(name));
-
-        let mutex = self.locks.lock(&meta_key);
-        let _guard = mutex.write();
+        self.list_push(name, val, false, None)
+    }
 
-        let mut meta = self.list_get_meta(name)?;
-        let ix = meta.push_front();
-        let item_key = IVec::from(keys::list(name, ix));
+    pub fn list_push_back(&self, name: &[u8], val: IVec) -> Result<(), Error> {
+        self.list_push(name, val, true, None)
+    }
 
-        if cfg!(feature = "safe") {
-            let mut batch = sled::Batch::default();
-            batch.insert(&item_key, Record::FromData(Tag::List, val).into_raw());
-            batch.insert(&meta_key, meta.encode().into_raw());
-            self.items.apply_batch(batch)?;
-        } else {
-            self.items
-                .insert(&item_key, Record::FromData(Tag::List, val).into_raw())?;
-            self.items.insert(&meta_key, meta.encode().into_raw())?;
-        }
+    /// Pushes `val` onto the front of `name` after capping it at `cap` elements, evicting from
+    /// the back in the same batch.
+    ///
+    /// Unlike [`Conn::list_set_cap`], this works on a missing or empty list, so a bounded list
+    /// that's always pushed to this way stays capped across being emptied.
+    pub fn list_push_front_capped(
+        &self,
+        name: &[u8],
+        val: IVec,
+        cap: NonZeroU64,
+    ) -> Result<(), Error> {
+        self.list_push(name, val, false, Some(cap))
+    }
 
-        Ok(())
+    /// Like [`Conn::list_push_front_capped`], but pushes onto the back and evicts from the front.
+    pub fn list_push_back_capped(
+        &self,
+        name: &[u8],
+        val: IVec,
+        cap: NonZeroU64,
+    ) -> Result<(), Error> {
+        self.list_push(name, val, true, Some(cap))
     }
 
-    pub fn list_push_back(&self, name: &[u8], val: IVec) -> Result<(), Error> {
+    // pushes `val` onto either end, storing `cap` as the list's cap first if it's given
+    fn list_push(
+        &self,
+        name: &[u8],
+        val: IVec,
+        back: bool,
+        cap: Option<NonZeroU64>,
+    ) -> Result<(), Error> {
         let meta_key = IVec::from(keys::list_meta(name));
 
         let mutex = self.locks.lock(&meta_key);
         let _guard = mutex.write();
 
+        self.reap_if_expired(&meta_key)?;
+
         let mut meta = self.list_get_meta(name)?;
-        let ix = meta.push_back();
+        if cap.is_some() {
+            meta.set_cap(cap);
+        }
+        let evicted = self.list_make_room(name, &mut meta, !back)?;
+        let ix = if back {
+            meta.push_back()
+        } else {
+            meta.push_front()
+        };
         let item_key = IVec::from(keys::list(name, ix));
 
         if cfg!(feature = "safe") {
             let mut batch = sled::Batch::default();
+            // evicted first, the new element may reuse an evicted key
+            for key in evicted {
+                batch.remove(key);
+            }
             batch.insert(&item_key, Record::FromData(Tag::List, val).into_raw());
             batch.insert(&meta_key, meta.encode().into_raw());
             self.items.apply_batch(batch)?;
         } else {
+            for key in evicted {
+                self.items.remove(key)?;
+            }
             self.items
                 .insert(&item_key, Record::FromData(Tag::List, val).into_raw())?;
             self.items.insert(&meta_key, meta.encode().into_raw())?;
         }
 
+        self.notifier.notify(&meta_key);
         Ok(())
     }
 
@@ -97,8 +406,10 @@ impl Conn {
         let mutex = self.locks.lock(&meta_key);
         let _guard = mutex.write();
 
+        self.reap_if_expired(&meta_key)?;
+
         let mut meta = self.list_get_meta(name)?;
-        if let Some(ix) = meta.pop_front() {
+        if let Some(ix) = self.list_pop_ix(name, &mut meta, false)? {
             let item_key = keys::list(name, ix);
             let old = self
                 .get_record(&keys::list(name, ix))?
@@ -116,8 +427,8 @@ impl Conn {
 
                 batch.remove(item_key);
 
-                if meta.len() > 0 {
-                    batch.insert(&meta_key, &meta.encode().into_raw());
+                if !meta.is_empty() {
+                    batch.insert(&meta_key, meta.encode().into_raw());
                 } else {
                     batch.remove(&meta_key)
                 }
@@ -125,13 +436,17 @@ impl Conn {
                 self.items.apply_batch(batch)?;
             } else {
                 self.items.remove(item_key)?;
-                if meta.len() > 0 {
-                    self.items.insert(&meta_key, &meta.encode().into_raw())?;
+                if !meta.is_empty() {
+                    self.items.insert(&meta_key, meta.encode().into_raw())?;
                 } else {
                     self.items.remove(&meta_key)?;
                 }
             }
 
+            if meta.is_empty() {
+                self.clear_deadline(&meta_key)?;
+            }
+
             Ok(old)
         } else {
             Ok(None)
@@ -144,8 +459,10 @@ impl Conn {
         let mutex = self.locks.lock(&meta_key);
         let _guard = mutex.write();
 
+        self.reap_if_expired(&meta_key)?;
+
         let mut meta = self.list_get_meta(name)?;
-        if let Some(ix) = meta.pop_back() {
+        if let Some(ix) = self.list_pop_ix(name, &mut meta, true)? {
             let item_key = keys::list(name, ix);
             let old = self
                 .get_record(&keys::list(name, ix))?
@@ -163,8 +480,8 @@ impl Conn {
 
                 batch.remove(item_key);
 
-                if meta.len() > 0 {
-                    batch.insert(&meta_key, &meta.encode().into_raw());
+                if !meta.is_empty() {
+                    batch.insert(&meta_key, meta.encode().into_raw());
                 } else {
                     batch.remove(&meta_key)
                 }
@@ -172,19 +489,26 @@ impl Conn {
                 self.items.apply_batch(batch)?;
             } else {
                 self.items.remove(item_key)?;
-                if meta.len() > 0 {
-                    self.items.insert(&meta_key, &meta.encode().into_raw())?;
+                if !meta.is_empty() {
+                    self.items.insert(&meta_key, meta.encode().into_raw())?;
                 } else {
                     self.items.remove(&meta_key)?;
                 }
             }
 
+            if meta.is_empty() {
+                self.clear_deadline(&meta_key)?;
+            }
+
             Ok(old)
         } else {
             Ok(None)
         }
     }
 
+    /// Replaces the element at position `ix`, returning the old one.
+    ///
+    /// Finding the position costs the same as in [`Conn::list_get`].
     pub fn list_set(&self, name: &[u8], ix: i64, val: IVec) -> Result<Option<IVec>, Error> {
         let meta_key = IVec::from(keys::list_meta(name));
 
@@ -194,7 +518,7 @@ impl Conn {
         let meta = self.list_get_meta(name)?;
         let iv = Record::FromData(Tag::List, val).into_raw();
 
-        if let Some(ix) = meta.mk_key(ix) {
+        if let Some(ix) = self.list_ix(name, &meta, ix)? {
             Ok(self
                 .items
                 .fetch_and_update(keys::list(name, ix), move |_| Some(iv.clone()))?)
@@ -204,89 +528,12 @@ impl Conn {
     }
 }
 
-// impl<S> ListRangeStore for S
-// where
-//     S: RangeStore,
-//     S::Error: From<Error>,
-// {
-//     type ListRangeIter = Box<dyn DoubleEndedIterator<Item = Result<IVec, Self::Error>> + 'static>;
-
-//     fn list_range<R: RangeBounds<u64>>(
-//         &self,
-//         name: &[u8],
-//         range: R,
-//     ) -> Result<Option<Self::ListRangeIter>, Self::Error> {
-//         use std::ops::Bound;
-
-//         let meta = self.list_get_meta(name)?;
-
-//         let start_key;
-//         let start = match range.start_bound() {
-//             Bound::Included(u) => {
-//                 if let Some(k) = meta.mk_key(*u) {
-//                     start_key = keys::list(name, k);
-//                     Bound::Included(start_key.as_slice())
-//                 } else {
-//                     return Ok(None);
-//                 }
-//             }
-//             Bound::Excluded(u) => {
-//                 if let Some(k) = meta.mk_key(*u) {
-//                     start_key = keys::list(name, k);
-//                     Bound::Excluded(start_key.as_slice())
-//                 } else {
-//                     return Ok(None);
-//                 }
-//             }
-//             Bound::Unbounded => {
-//                 if let Some(k) = meta.mk_key(0) {
-//                     start_key = keys::list(name, k);
-//                     Bound::Included(start_key.as_slice())
-//                 } else {
-//                     return Ok(None);
-//                 }
-//             }
-//         };
-
-//         let end_key;
-//         let end = match range.end_bound() {
-//             Bound::Included(u) => {
-//                 if let Some(k) = meta.mk_key(*u) {
-//                     end_key = keys::list(name, k);
-//                     Bound::Included(end_key.as_slice())
-//                 } else {
-//                     return Ok(None);
-//                 }
-//             }
-//             Bound::Excluded(u) => {
-//                 if let Some(k) = meta.mk_key(*u) {
-//                     end_key = keys::list(name, k);
-//                     Bound::Excluded(end_key.as_slice())
-//                 } else {
-//                     return Ok(None);
-//                 }
-//             }
-//             Bound::Unbounded => {
-//                 if let Some(k) = meta.mk_key(meta.len() - 1) {
-//                     end_key = keys::list(name, k);
-//                     Bound::Included(end_key.as_slice())
-//                 } else {
-//                     return Ok(None);
-//                 }
-//             }
-//         };
-
-//         let iter = self.range((start, end)).map(|res| res.map(|t| t.1));
-//         let biter = Box::new(iter);
-
-//         Ok(Some(biter))
-//     }
-// }
-
 #[derive(Error, Debug)]
 pub enum ListError {
     #[error("invalid list metadata, key was: {0:#?}")]
     InvalidMeta(IVec),
     #[error("missing value in list {0:#?} at index {1}")]
     MissingVal(Vec<u8>, ListIndex),
+    #[error("list position rank can't be zero")]
+    ZeroRank,
 }
@@ -0,0 +1,88 @@
+use super::*;
+
+/// One end of a list.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ListEnd {
+    Front,
+    Back,
+}
+
+impl Conn {
+    /// Pops an element from the `from_end` of `src` and pushes it onto the `to_end` of `dst`,
+    /// returning it, or `None` if `src` is empty.
+    ///
+    /// Both lists are locked for the duration and every write lands in a single batch, so the
+    /// element is always in exactly one of them. A capped `dst` evicts like a push would.
+    /// `src` and `dst` may be the same list, which rotates it.
+    pub fn list_move(
+        &self,
+        src: &[u8],
+        dst: &[u8],
+        from_end: ListEnd,
+        to_end: ListEnd,
+    ) -> Result<Option<IVec>, Error> {
+        let src_key = IVec::from(keys::list_meta(src));
+        let dst_key = IVec::from(keys::list_meta(dst));
+
+        let (first, second) = self.locks.lock_pair(&src_key, &dst_key);
+        let _first = first.write();
+        let _second = second.as_ref().map(|lock| lock.write());
+
+        self.reap_if_expired(&src_key)?;
+        self.reap_if_expired(&dst_key)?;
+
+        let mut src_meta = self.list_get_meta(src)?;
+        // read before popping, so a `dst` holding another type fails without side effects
+        let mut dst_meta = self.list_get_meta(dst)?;
+
+        let from_ix = match self.list_pop_ix(src, &mut src_meta, from_end == ListEnd::Back)? {
+            Some(ix) => ix,
+            None => return Ok(None),
+        };
+        let from_key = keys::list(src, from_ix);
+
+        let raw = match self.items.get(&from_key)? {
+            Some(raw) => raw,
+            None => return Err(ListError::MissingVal(src.to_vec(), from_ix).into()),
+        };
+        let val = decode_item(raw.clone())?;
+
+        let evicted = if src_key == dst_key {
+            dst_meta = src_meta;
+            Vec::new()
+        } else {
+            self.list_make_room(dst, &mut dst_meta, to_end == ListEnd::Front)?
+        };
+
+        let to_ix = match to_end {
+            ListEnd::Front => dst_meta.push_front(),
+            ListEnd::Back => dst_meta.push_back(),
+        };
+
+        let mut batch = sled::Batch::default();
+        // removed first, so a rotation landing on the same key keeps the element
+        batch.remove(from_key);
+        for key in evicted {
+            batch.remove(key);
+        }
+        batch.insert(keys::list(dst, to_ix), raw);
+
+        if src_key != dst_key {
+            if src_meta.is_empty() {
+                batch.remove(&src_key);
+            } else {
+                batch.insert(&src_key, src_meta.encode().into_raw());
+            }
+        }
+        batch.insert(&dst_key, dst_meta.encode().into_raw());
+
+        self.items.apply_batch(batch)?;
+
+        if src_key != dst_key && src_meta.is_empty() {
+            self.clear_deadline(&src_key)?;
+        }
+
+        self.notifier.notify(&dst_key);
+        Ok(Some(val))
+    }
+}
@@ -0,0 +1,194 @@
+use super::*;
+use std::ops::Bound;
+
+pub type ListRangeIter = Box<dyn DoubleEndedIterator<Item = Result<IVec, Error>> + Send>;
+
+// iterates over the elements of a list with indices in `lo..hi`. the front reads through a
+// sled range, the back finds each element with prev_ix, since sled's own reverse iteration
+// isn't reliable over list keys.
+struct ListRange {
+    items: sled::Tree,
+    name: Vec<u8>,
+    front: sled::Iter,
+    lo: ListIndex,
+    hi: ListIndex,
+}
+
+impl Iterator for ListRange {
+    type Item = Result<IVec, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.lo >= self.hi {
+            return None;
+        }
+
+        let (key, raw) = match self.front.next()? {
+            Ok(entry) => entry,
+            Err(e) => {
+                self.lo = self.hi;
+                return Some(Err(e.into()));
+            }
+        };
+
+        let ix = key_ix(&key);
+        if ix >= self.hi {
+            self.lo = self.hi;
+            return None;
+        }
+
+        self.lo = ix + 1;
+        Some(decode_item(raw))
+    }
+}
+
+impl DoubleEndedIterator for ListRange {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let found = prev_ix(&self.items, &self.name, self.lo, self.hi).and_then(|ix| match ix {
+            Some(ix) => Ok(Some((ix, self.items.get(keys::list(&self.name, ix))?))),
+            None => Ok(None),
+        });
+
+        match found {
+            Ok(Some((ix, Some(raw)))) => {
+                self.hi = ix;
+                Some(decode_item(raw))
+            }
+            Ok(Some((ix, None))) => {
+                self.hi = self.lo;
+                Some(Err(ListError::MissingVal(self.name.clone(), ix).into()))
+            }
+            Ok(None) => {
+                self.hi = self.lo;
+                None
+            }
+            Err(e) => {
+                self.hi = self.lo;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl Conn {
+    /// Iterates over the elements of `name` from position `start` to `stop` inclusive.
+    ///
+    /// Negative positions count from the back, so `list_range(name, 0, -1)` is the whole list,
+    /// and out of range positions are clamped.
+    /// Elements are read lazily, so writes made while iterating may be observed.
+    pub fn list_range(&self, name: &[u8], start: i64, stop: i64) -> Result<ListRangeIter, Error> {
+        let meta_key = IVec::from(keys::list_meta(name));
+
+        let mutex = self.locks.lock(&meta_key);
+        let _guard = mutex.read();
+
+        let meta = self.list_get_meta(name)?;
+
+        let (start, stop) = match meta.range(start, stop) {
+            Some((start, stop)) => (start as i64, stop as i64),
+            None => return Ok(Box::new(std::iter::empty())),
+        };
+
+        match (
+            self.list_ix(name, &meta, start)?,
+            self.list_ix(name, &meta, stop)?,
+        ) {
+            (Some(first), Some(last)) => {
+                let iter = ListRange {
+                    items: self.items.clone(),
+                    name: name.to_vec(),
+                    front: self
+                        .items
+                        .range(keys::list(name, first)..=keys::list(name, last)),
+                    lo: first,
+                    hi: last + 1,
+                };
+                Ok(Box::new(iter))
+            }
+            _ => Ok(Box::new(std::iter::empty())),
+        }
+    }
+
+    /// Trims `name` to the elements from position `start` to `stop` inclusive,
+    /// with the same position semantics as [`Conn::list_range`].
+    ///
+    /// If elements were inserted in the middle of the list, the survivors are re-indexed in the
+    /// same batch, so positions map directly to indices again.
+    /// Removes the list if no elements are left.
+    pub fn list_trim(&self, name: &[u8], start: i64, stop: i64) -> Result<(), Error> {
+        let meta_key = IVec::from(keys::list_meta(name));
+
+        let mutex = self.locks.lock(&meta_key);
+        let _guard = mutex.write();
+
+        self.reap_if_expired(&meta_key)?;
+
+        let mut meta = self.list_get_meta(name)?;
+
+        let (head, tail) = match (meta.head_ix(), meta.tail_ix()) {
+            (Some(head), Some(tail)) => (head, tail),
+            _ => return Ok(()),
+        };
+
+        let (start, stop) = match meta.range(start, stop) {
+            Some(range) => range,
+            None => {
+                self.purge_item(&meta_key)?;
+                return Ok(());
+            }
+        };
+
+        let (first, last) = match (
+            self.list_ix(name, &meta, start as i64)?,
+            self.list_ix(name, &meta, stop as i64)?,
+        ) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Ok(()),
+        };
+
+        let mut batch = sled::Batch::default();
+
+        let before = self
+            .items
+            .range(keys::list(name, head)..keys::list(name, first));
+        let after = self.items.range::<&[u8], _>((
+            Bound::Excluded(keys::list(name, last).as_slice()),
+            Bound::Included(keys::list(name, tail).as_slice()),
+        ));
+
+        for entry in before.chain(after) {
+            let (key, _) = entry?;
+            batch.remove(key);
+        }
+
+        let len = stop - start + 1;
+        if meta.is_regular() {
+            meta.set_bounds(first, last, len);
+        } else {
+            // the trim is already a write, so take the chance to re-index the survivors
+            // regularly, making positions map directly to indices again
+            meta.set_regular(first, len);
+            let stride = meta.stride() as ListIndex;
+
+            let survivors = self
+                .items
+                .range(keys::list(name, first)..=keys::list(name, last));
+            // the batch keeps the last write to each key, so new keys override removals
+            let mut moved = Vec::new();
+            for (i, entry) in (0..).zip(survivors) {
+                let (key, raw) = entry?;
+                let new_key = keys::list(name, first + i * stride);
+                if key.as_ref() != new_key.as_slice() {
+                    batch.remove(key);
+                    moved.push((new_key, raw));
+                }
+            }
+            for (key, raw) in moved {
+                batch.insert(key, raw);
+            }
+        }
+        batch.insert(&meta_key, meta.encode().into_raw());
+
+        self.items.apply_batch(batch)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+use super::*;
+use std::collections::HashSet;
+
+impl Conn {
+    /// Removes elements equal to `value` from `name`.
+    ///
+    /// A positive `count` removes up to `count` matches scanning from the front, a negative
+    /// `count` removes up to `-count` matches scanning from the back, and zero removes every match.
+    /// The remaining elements are re-indexed so the list is regular again.
+    ///
+    /// Returns the number of elements removed.
+    pub fn list_remove(&self, name: &[u8], count: i64, value: &[u8]) -> Result<u64, Error> {
+        let meta_key = IVec::from(keys::list_meta(name));
+
+        let mutex = self.locks.lock(&meta_key);
+        let _guard = mutex.write();
+
+        self.reap_if_expired(&meta_key)?;
+
+        let mut meta = self.list_get_meta(name)?;
+        let (head, tail) = match (meta.head_ix(), meta.tail_ix()) {
+            (Some(head), Some(tail)) => (head, tail),
+            _ => return Ok(0),
+        };
+
+        let items = self
+            .items
+            .range(keys::list(name, head)..=keys::list(name, tail))
+            .map(|res| {
+                let (key, val) = res?;
+                Ok((key, decode_item(val)?))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let from_back = count < 0;
+        let limit = if count == 0 {
+            u64::MAX
+        } else {
+            count.unsigned_abs()
+        };
+
+        let mut keep = vec![true; items.len()];
+        let mut removed = 0;
+
+        for i in 0..items.len() {
+            if removed == limit {
+                break;
+            }
+
+            let pos = if from_back { items.len() - 1 - i } else { i };
+            if items[pos].1 == value {
+                keep[pos] = false;
+                removed += 1;
+            }
+        }
+
+        if removed == 0 {
+            return Ok(0);
+        }
+
+        if removed == meta.len() {
+            self.purge_item(&meta_key)?;
+            return Ok(removed);
+        }
+
+        // survivors are packed against one end, so in a regular list only the survivors between
+        // the other end's outermost match and that end move. pick the end that moves fewer.
+        let first = keep
+            .iter()
+            .position(|keep| !keep)
+            .expect("something was removed");
+        let last = keep
+            .iter()
+            .rposition(|keep| !keep)
+            .expect("something was removed");
+        let moved_packing_front = keep[first..].iter().filter(|keep| **keep).count();
+        let moved_packing_back = keep[..last].iter().filter(|keep| **keep).count();
+        meta.compact(removed, moved_packing_back < moved_packing_front);
+        let new_head = meta.head_ix().expect("list can't be empty");
+
+        let mut batch = sled::Batch::default();
+
+        let survivors = items
+            .iter()
+            .zip(keep.iter())
+            .filter(|(_, keep)| **keep)
+            .map(|(item, _)| item);
+
+        let stride = meta.stride() as ListIndex;
+        let mut new_keys = HashSet::new();
+
+        for (i, (key, val)) in survivors.enumerate() {
+            let new_key = keys::list(name, new_head + i as ListIndex * stride);
+            if key.as_ref() != new_key.as_slice() {
+                batch.insert(
+                    new_key.as_slice(),
+                    Record::FromData(Tag::List, val.clone()).into_raw(),
+                );
+            }
+            new_keys.insert(new_key);
+        }
+
+        for (key, _) in items.iter() {
+            if !new_keys.contains(key.as_ref()) {
+                batch.remove(key);
+            }
+        }
+
+        batch.insert(&meta_key, meta.encode().into_raw());
+        self.items.apply_batch(batch)?;
+
+        Ok(removed)
+    }
+
+    /// Finds the positions of elements equal to `value` in `name`.
+    ///
+    /// Positions are counted from the front. A positive `rank` of `n` skips the first `n - 1`
+    /// matches scanning from the front, a negative `rank` does the same scanning from the back.
+    /// Up to `count` positions are returned, or every match if `count` is zero, in scan order.
+    /// A non-zero `maxlen` limits the scan to that many elements.
+    pub fn list_position(
+        &self,
+        name: &[u8],
+        value: &[u8],
+        rank: i64,
+        count: u64,
+        maxlen: u64,
+    ) -> Result<Vec<u64>, Error> {
+        if rank == 0 {
+            return Err(ListError::ZeroRank.into());
+        }
+
+        let meta_key = IVec::from(keys::list_meta(name));
+
+        let mutex = self.locks.lock(&meta_key);
+        let _guard = mutex.read();
+
+        let meta = self.list_get_meta(name)?;
+        let (head, tail) = match (meta.head_ix(), meta.tail_ix()) {
+            (Some(head), Some(tail)) => (head, tail),
+            _ => return Ok(Vec::new()),
+        };
+
+        let from_back = rank < 0;
+        let skip = rank.unsigned_abs() - 1;
+        let count = if count == 0 { u64::MAX } else { count };
+        let maxlen = if maxlen == 0 { u64::MAX } else { maxlen };
+
+        let elems: Box<dyn Iterator<Item = Result<_, Error>>> = if from_back {
+            Box::new(self.list_iter_back(name, head, tail + 1))
+        } else {
+            Box::new(
+                self.items
+                    .range(keys::list(name, head)..=keys::list(name, tail))
+                    .map(|entry| Ok(entry?)),
+            )
+        };
+
+        let mut found = Vec::new();
+        let mut matched = 0;
+
+        for (i, entry) in (0..maxlen).zip(elems) {
+            if found.len() as u64 == count {
+                break;
+            }
+
+            if decode_item(entry?.1)? == value {
+                matched += 1;
+                if matched > skip {
+                    found.push(if from_back { meta.len() - 1 - i } else { i });
+                }
+            }
+        }
+
+        Ok(found)
+    }
+}
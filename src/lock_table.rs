@@ -1,11 +1,14 @@
 use dashmap::DashMap;
 use parking_lot::RwLock;
 
-use std::{ops::Deref, sync::atomic::*};
+use std::{
+    ops::Deref,
+    sync::{atomic::*, Arc},
+};
 
 #[derive(Default)]
 pub struct Table {
-    inner: DashMap<sled::IVec, LockEntryInner>,
+    inner: DashMap<sled::IVec, Arc<LockEntryInner>>,
 }
 
 struct LockEntryInner {
@@ -22,10 +25,10 @@ impl Default for LockEntryInner {
     }
 }
 
-type TableRef<'a> = dashmap::mapref::one::Ref<'a, sled::IVec, LockEntryInner>;
-
+// entries hold an `Arc` rather than a map ref, so holding one doesn't keep its shard locked,
+// which would deadlock a thread taking a second lock in the same shard
 pub struct LockEntry<'a> {
-    inner: Option<TableRef<'a>>,
+    inner: Arc<LockEntryInner>,
     key: &'a sled::IVec,
     table: &'a Table,
 }
@@ -34,15 +37,13 @@ impl<'a> Deref for LockEntry<'a> {
     type Target = RwLock<()>;
 
     fn deref(&self) -> &RwLock<()> {
-        &self.inner.as_ref().unwrap().lock
+        &self.inner.lock
     }
 }
 
 impl<'a> Drop for LockEntry<'a> {
     fn drop(&mut self) {
-        let item = self.inner.take().unwrap();
-        if item.refcount.fetch_sub(1, Ordering::AcqRel) == 1 {
-            drop(item);
+        if self.inner.refcount.fetch_sub(1, Ordering::AcqRel) == 1 {
             self.table.inner.remove_if(self.key, |_, item| {
                 item.refcount.load(Ordering::Acquire) == 0
             });
@@ -51,24 +52,44 @@ impl<'a> Drop for LockEntry<'a> {
 }
 
 impl Table {
-    pub fn lock<'a>(self: &'a Self, key: &'a sled::IVec) -> LockEntry<'a> {
+    pub fn lock<'a>(&'a self, key: &'a sled::IVec) -> LockEntry<'a> {
+        // the refcount is always bumped while the shard is locked,
+        // so `remove_if` in `drop` can't race with it
         let inner = {
             // first we try a shared get, to not contend the map
             if let Some(r) = self.inner.get(key) {
                 r.refcount.fetch_add(1, Ordering::AcqRel);
-                r
+                r.clone()
             } else {
                 // if that fails, we'll get the entry with an exclusive lock
-                let mut entry = self.inner.entry(key.clone()).or_default();
-                *entry.refcount.get_mut() += 1;
-                entry.downgrade()
+                let entry = self.inner.entry(key.clone()).or_default();
+                entry.refcount.fetch_add(1, Ordering::AcqRel);
+                entry.clone()
             }
         };
 
         LockEntry {
-            inner: Some(inner),
+            inner,
             key,
             table: self,
         }
     }
+
+    /// Locks two keys in a consistent order, so two threads locking the same pair can't deadlock.
+    ///
+    /// Guards must be taken in the order the entries are returned.
+    /// The second entry is `None` if both keys are the same.
+    pub fn lock_pair<'a>(
+        &'a self,
+        a: &'a sled::IVec,
+        b: &'a sled::IVec,
+    ) -> (LockEntry<'a>, Option<LockEntry<'a>>) {
+        if a == b {
+            (self.lock(a), None)
+        } else if a < b {
+            (self.lock(a), Some(self.lock(b)))
+        } else {
+            (self.lock(b), Some(self.lock(a)))
+        }
+    }
 }
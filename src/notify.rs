@@ -0,0 +1,111 @@
+use parking_lot::{Condvar, Mutex};
+use sled::IVec;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+// wakes threads blocked on a list when an element may have been pushed to it.
+//
+// waiters register under the meta keys of their lists before checking them, and pushes notify
+// while still holding the list's write lock, so a push landing between the check and the wait is
+// never missed. a push only wakes the waiters on its own list, and they race to pop under the
+// list's write lock, so each element goes to exactly one. while nobody waits, notifying is a
+// single atomic load.
+#[derive(Default)]
+pub struct Notifier {
+    waiting: AtomicUsize,
+    waiters: Mutex<HashMap<IVec, Vec<Arc<Waiter>>>>,
+}
+
+#[derive(Default)]
+struct Waiter {
+    woken: Mutex<bool>,
+    cond: Condvar,
+}
+
+pub struct Registration<'a> {
+    notifier: &'a Notifier,
+    keys: Vec<IVec>,
+    waiter: Arc<Waiter>,
+}
+
+impl Notifier {
+    // registers a waiter on every key in `keys`, until the registration is dropped
+    pub fn register(&self, keys: Vec<IVec>) -> Registration<'_> {
+        let waiter = Arc::new(Waiter::default());
+
+        let mut waiters = self.waiters.lock();
+        for key in &keys {
+            waiters.entry(key.clone()).or_default().push(waiter.clone());
+        }
+        self.waiting.fetch_add(1, Ordering::SeqCst);
+
+        Registration {
+            notifier: self,
+            keys,
+            waiter,
+        }
+    }
+
+    // wakes every waiter registered on `key`,
+    // the caller must hold the write lock for `key` so registering waiters can't miss it
+    pub fn notify(&self, key: &[u8]) {
+        if self.waiting.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+
+        if let Some(waiters) = self.waiters.lock().get(key) {
+            for waiter in waiters {
+                *waiter.woken.lock() = true;
+                waiter.cond.notify_one();
+            }
+        }
+    }
+}
+
+impl<'a> Registration<'a> {
+    // blocks until one of the keys is notified, returning `false` if `deadline` passes first
+    pub fn wait(&self, deadline: Option<Instant>) -> bool {
+        let mut woken = self.waiter.woken.lock();
+
+        while !*woken {
+            match deadline {
+                Some(deadline) => {
+                    if self
+                        .waiter
+                        .cond
+                        .wait_until(&mut woken, deadline)
+                        .timed_out()
+                    {
+                        return *woken;
+                    }
+                }
+                None => self.waiter.cond.wait(&mut woken),
+            }
+        }
+
+        true
+    }
+}
+
+impl<'a> Drop for Registration<'a> {
+    fn drop(&mut self) {
+        let mut waiters = self.notifier.waiters.lock();
+
+        for key in &self.keys {
+            if let Some(registered) = waiters.get_mut(key) {
+                registered.retain(|waiter| !Arc::ptr_eq(waiter, &self.waiter));
+                if registered.is_empty() {
+                    waiters.remove(key);
+                }
+            }
+        }
+
+        self.notifier.waiting.fetch_sub(1, Ordering::SeqCst);
+    }
+}
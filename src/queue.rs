@@ -0,0 +1,244 @@
+use super::*;
+use expiry::*;
+use std::{
+    convert::TryFrom,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use thiserror::*;
+
+/// A work queue where dequeued items are leased rather than removed.
+///
+/// Items wait in the list `pending`. [`Queue::dequeue`] moves an item into the table `leases`,
+/// where it stays hidden until it's acknowledged with [`Queue::ack`] or its visibility timeout
+/// passes, at which point it goes back to the front of `pending`.
+/// Items are delivered at least once: a worker that outlives its lease may see its item
+/// handed to another worker.
+#[derive(Clone)]
+pub struct Queue {
+    conn: Conn,
+    pending: Vec<u8>,
+    leases: Vec<u8>,
+}
+
+/// An item handed out by [`Queue::dequeue`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Lease {
+    /// The deadline in milliseconds followed by a unique sequence number, so leases are stored
+    /// in deadline order.
+    pub id: u128,
+    pub item: IVec,
+    /// When the item goes back to the queue if it hasn't been acknowledged.
+    pub deadline: SystemTime,
+}
+
+// leases are stored as the deadline followed by the item
+fn encode_lease(deadline: u64, item: &[u8]) -> IVec {
+    let mut out = Vec::with_capacity(DEADLINE_BYTES + item.len());
+    out.extend_from_slice(&encode_deadline(deadline));
+    out.extend_from_slice(item);
+    out.into()
+}
+
+fn decode_lease(raw: IVec) -> Result<(u64, IVec), Error> {
+    let rec = Record::decode(raw)?;
+    if rec.tag() != Tag::Table {
+        Err(Error::BadType(Tag::Table, rec.tag()))?
+    }
+
+    let data = rec.data();
+    match data.get(..DEADLINE_BYTES).and_then(decode_deadline) {
+        Some(deadline) => Ok((
+            deadline,
+            data.subslice(DEADLINE_BYTES, data.len() - DEADLINE_BYTES),
+        )),
+        None => Err(QueueError::InvalidLease(data).into()),
+    }
+}
+
+impl Conn {
+    /// A [`Queue`] keeping its items in the list `pending` and its leases in the table `leases`.
+    ///
+    /// Fails with [`QueueError::SameName`] if `pending` and `leases` are the same name.
+    pub fn queue(&self, pending: &[u8], leases: &[u8]) -> Result<Queue, Error> {
+        if pending == leases {
+            return Err(QueueError::SameName(pending.to_vec()).into());
+        }
+
+        Ok(Queue {
+            conn: self.clone(),
+            pending: pending.to_vec(),
+            leases: leases.to_vec(),
+        })
+    }
+}
+
+impl Queue {
+    pub fn enqueue(&self, item: IVec) -> Result<(), Error> {
+        self.conn.list_push_back(&self.pending, item)
+    }
+
+    /// Leases the item at the front of the queue, hiding it for `visibility`.
+    ///
+    /// Expired leases are returned to the queue first.
+    /// Returns `None` if there's nothing to hand out.
+    pub fn dequeue(&self, visibility: Duration) -> Result<Option<Lease>, Error> {
+        let pending_key = IVec::from(keys::list_meta(&self.pending));
+        let leases_key = IVec::from(keys::table_meta(&self.leases));
+
+        let (first, second) = self.conn.locks.lock_pair(&pending_key, &leases_key);
+        let _first = first.write();
+        let _second = second.as_ref().map(|lock| lock.write());
+
+        self.requeue_locked(&pending_key, &leases_key)?;
+
+        let mut pending = self.conn.list_get_meta(&self.pending)?;
+        let mut leases = self.conn.table_get_meta(&self.leases)?;
+
+        let ix = match self.conn.list_pop_ix(&self.pending, &mut pending, false)? {
+            Some(ix) => ix,
+            None => return Ok(None),
+        };
+        let item_key = keys::list(&self.pending, ix);
+        let item = match self.conn.items.get(&item_key)? {
+            Some(raw) => list::decode_item(raw)?,
+            None => return Err(list::ListError::MissingVal(self.pending.clone(), ix).into()),
+        };
+
+        let visibility = u64::try_from(visibility.as_millis()).unwrap_or(u64::MAX);
+        let deadline = now_millis().saturating_add(visibility);
+        let id = (deadline as u128) << 64 | self.conn.db.generate_id()? as u128;
+        leases.len += 1;
+
+        let mut batch = sled::Batch::default();
+        batch.remove(item_key);
+        if pending.is_empty() {
+            batch.remove(&pending_key);
+        } else {
+            batch.insert(&pending_key, pending.encode().into_raw());
+        }
+        batch.insert(
+            keys::table(&self.leases, &id.to_be_bytes()),
+            Record::FromData(Tag::Table, encode_lease(deadline, &item)).into_raw(),
+        );
+        batch.insert(&leases_key, leases.encode().into_raw());
+        self.conn.items.apply_batch(batch)?;
+
+        if pending.is_empty() {
+            self.conn.clear_deadline(&pending_key)?;
+        }
+
+        Ok(Some(Lease {
+            id,
+            item,
+            deadline: UNIX_EPOCH + Duration::from_millis(deadline),
+        }))
+    }
+
+    /// Deletes the leased item `id` for good.
+    ///
+    /// Acknowledging after the visibility timeout still succeeds if the item hasn't been
+    /// returned to the queue yet. Returns `false` if there's no such lease.
+    pub fn ack(&self, id: u128) -> Result<bool, Error> {
+        Ok(self
+            .conn
+            .table_remove(&self.leases, &id.to_be_bytes())?
+            .is_some())
+    }
+
+    /// Returns every item whose lease has expired to the front of the queue,
+    /// earliest deadline first, returning how many there were.
+    ///
+    /// Requeued items ignore any cap on `pending`, so leased work is never dropped. A full
+    /// capped queue ends up over its cap until later pushes evict it back down.
+    pub fn requeue_expired(&self) -> Result<usize, Error> {
+        let pending_key = IVec::from(keys::list_meta(&self.pending));
+        let leases_key = IVec::from(keys::table_meta(&self.leases));
+
+        let (first, second) = self.conn.locks.lock_pair(&pending_key, &leases_key);
+        let _first = first.write();
+        let _second = second.as_ref().map(|lock| lock.write());
+
+        self.requeue_locked(&pending_key, &leases_key)
+    }
+
+    // the caller must hold the write locks for both keys
+    fn requeue_locked(&self, pending_key: &IVec, leases_key: &IVec) -> Result<usize, Error> {
+        self.conn.reap_if_expired(pending_key)?;
+        self.conn.table_reap(leases_key)?;
+
+        let now = now_millis();
+        let mut expired = Vec::new();
+
+        // lease ids start with their deadline, so only the due leases are read
+        for entry in self.conn.items.scan_prefix(leases_key) {
+            let (key, raw) = entry?;
+            if key == leases_key {
+                continue;
+            }
+
+            let (deadline, item) = decode_lease(raw)?;
+            if deadline > now {
+                break;
+            }
+            expired.push((key, item));
+        }
+
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        let mut pending = self.conn.list_get_meta(&self.pending)?;
+        let mut leases = self.conn.table_get_meta(&self.leases)?;
+        leases.len -= expired.len() as u64;
+
+        let mut batch = sled::Batch::default();
+
+        // leases are in deadline order, so pushing the latest first leaves the earliest
+        // at the front
+        for (key, item) in expired.iter().rev() {
+            let ix = pending.push_front();
+            batch.insert(
+                keys::list(&self.pending, ix),
+                Record::FromData(Tag::List, item.clone()).into_raw(),
+            );
+            batch.remove(key);
+        }
+
+        batch.insert(pending_key, pending.encode().into_raw());
+        if leases.is_empty() {
+            batch.remove(leases_key);
+        } else {
+            batch.insert(leases_key, leases.encode().into_raw());
+        }
+        self.conn.items.apply_batch(batch)?;
+
+        if leases.is_empty() {
+            self.conn.clear_deadline(leases_key)?;
+        }
+
+        self.conn.notifier.notify(pending_key);
+        Ok(expired.len())
+    }
+
+    /// The number of items waiting to be dequeued.
+    pub fn len(&self) -> Result<u64, Error> {
+        self.conn.list_len(&self.pending)
+    }
+
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.len()? == 0)
+    }
+
+    /// The number of items leased out and not yet acknowledged.
+    pub fn in_flight(&self) -> Result<u64, Error> {
+        Ok(self.conn.table_get_meta(&self.leases)?.len())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum QueueError {
+    #[error("invalid queue lease: {0:#?}")]
+    InvalidLease(IVec),
+    #[error("queue items and leases can't share the name {0:?}")]
+    SameName(Vec<u8>),
+}
@@ -0,0 +1,126 @@
+use super::*;
+use std::collections::BTreeMap;
+
+impl Conn {
+    /// Reads every field in `keys` of table `name`, in order, `None` where a field is missing.
+    pub fn table_get_many<K: AsRef<[u8]>, I: IntoIterator<Item = K>>(
+        &self,
+        name: &[u8],
+        keys: I,
+    ) -> Result<Vec<Option<IVec>>, Error> {
+        let meta_key = IVec::from(keys::table_meta(name));
+
+        let mutex = self.locks.lock(&meta_key);
+        let _guard = mutex.read();
+
+        if self.is_expired(&meta_key)? {
+            return Ok(keys.into_iter().map(|_| None).collect());
+        }
+
+        keys.into_iter()
+            .map(|key| {
+                let key = keys::table(name, key.as_ref());
+                if self.is_expired(&key)? {
+                    return Ok(None);
+                }
+                self.table_get_field(&key)
+            })
+            .collect()
+    }
+
+    /// Sets every field in `entries` of table `name`, returning how many fields were added.
+    ///
+    /// Later entries win over earlier ones for the same field.
+    /// The metadata is updated once and every field is written in a single batch.
+    pub fn table_insert_many<K: AsRef<[u8]>, I: IntoIterator<Item = (K, IVec)>>(
+        &self,
+        name: &[u8],
+        entries: I,
+    ) -> Result<u64, Error> {
+        let writes = entries
+            .into_iter()
+            .map(|(key, val)| (keys::table(name, key.as_ref()), Some(val)))
+            .collect();
+        let (added, _) = self.table_write_many(name, writes)?;
+        Ok(added)
+    }
+
+    /// Removes every field in `keys` of table `name`, returning how many fields were removed.
+    ///
+    /// The metadata is updated once and every field is removed in a single batch.
+    pub fn table_remove_many<K: AsRef<[u8]>, I: IntoIterator<Item = K>>(
+        &self,
+        name: &[u8],
+        keys: I,
+    ) -> Result<u64, Error> {
+        let writes = keys
+            .into_iter()
+            .map(|key| (keys::table(name, key.as_ref()), None))
+            .collect();
+        let (_, removed) = self.table_write_many(name, writes)?;
+        Ok(removed)
+    }
+
+    // sets or removes every field key in `writes`, returning how many fields were added and
+    // how many were removed
+    fn table_write_many(
+        &self,
+        name: &[u8],
+        writes: BTreeMap<Vec<u8>, Option<IVec>>,
+    ) -> Result<(u64, u64), Error> {
+        let meta_key = IVec::from(keys::table_meta(name));
+
+        let mutex = self.locks.lock(&meta_key);
+        let _guard = mutex.write();
+
+        self.table_reap(&meta_key)?;
+
+        let mut meta = self.table_raw_meta(&meta_key)?;
+        let mut batch = Batch::default();
+        let mut added = 0;
+        let mut removed = Vec::new();
+        let mut changed = false;
+
+        for (key, new) in writes {
+            let exists = self.table_get_field(&key)?.is_some();
+
+            match new {
+                Some(val) => {
+                    if !exists {
+                        added += 1;
+                    }
+                    batch.insert(key, Record::FromData(Tag::Table, val).into_raw());
+                    changed = true;
+                }
+                None if exists => {
+                    batch.remove(key.as_slice());
+                    removed.push(key);
+                    changed = true;
+                }
+                None => {}
+            }
+        }
+
+        if !changed {
+            return Ok((0, 0));
+        }
+
+        meta.len = meta.len + added - removed.len() as u64;
+        if !meta.is_empty() {
+            batch.insert(&meta_key, meta.encode().into_raw());
+        } else {
+            batch.remove(&meta_key);
+        }
+
+        self.items.apply_batch(batch)?;
+
+        for key in &removed {
+            self.clear_deadline(key)?;
+        }
+        if meta.is_empty() {
+            self.clear_deadline(&meta_key)?;
+        }
+
+        Ok((added, removed.len() as u64))
+    }
+}
@@ -1,23 +1,121 @@
 use super::*;
+use expiry::*;
 use sled::{Batch, IVec};
+use std::time::SystemTime;
 use thiserror::*;
 
 mod meta;
 pub use self::meta::*;
 
+mod many;
+mod numeric;
+
+mod range;
+pub use self::range::*;
+
 impl Conn {
+    /// Reads the metadata of `name`, leaving out fields whose expiry has passed but that
+    /// haven't been reaped yet.
+    ///
+    /// Finding those takes a pass over every field of `name` with an expiry, plus a lookup for
+    /// each expired one, so it's only a single lookup for tables whose fields don't expire,
+    /// like the lease tables of a [`queue::Queue`].
     pub fn table_get_meta(&self, name: &[u8]) -> Result<Meta, Error> {
         let key = keys::table_meta(name);
 
-        if let Some(bs) = self.get_record(&key)? {
+        if self.is_expired(&key)? {
+            return Ok(Meta::default());
+        }
+
+        let mut meta = self.table_raw_meta(&key)?;
+        let (expired, _) = self.table_expired_fields(&key)?;
+        meta.len = meta.len.saturating_sub(expired.len() as u64);
+        Ok(meta)
+    }
+
+    // reads the stored metadata, without accounting for expired fields
+    fn table_raw_meta(&self, meta_key: &[u8]) -> Result<Meta, Error> {
+        if let Some(bs) = self.get_record(meta_key)? {
             Meta::decode(&bs)
         } else {
             Ok(Meta::default())
         }
     }
 
+    // finds the fields of the table at `meta_key` whose deadlines have passed, split into those
+    // still stored and stale deadlines left behind by fields that are already gone
+    fn table_expired_fields(&self, meta_key: &[u8]) -> Result<(Vec<IVec>, Vec<IVec>), Error> {
+        let now = now_millis();
+        let mut expired = Vec::new();
+        let mut stale = Vec::new();
+
+        for entry in self.ttl.scan_prefix(meta_key) {
+            let (key, deadline) = entry?;
+            if key == meta_key || decode_deadline(&deadline).is_none_or(|d| d > now) {
+                continue;
+            }
+
+            if self.items.contains_key(&key)? {
+                expired.push(key);
+            } else {
+                stale.push(key);
+            }
+        }
+
+        Ok((expired, stale))
+    }
+
+    // removes the expired fields of the table at `meta_key`, and any stale deadlines,
+    // the caller must hold the write lock for `meta_key`
+    pub(crate) fn table_reap_fields(&self, meta_key: &[u8]) -> Result<usize, Error> {
+        let (expired, stale) = self.table_expired_fields(meta_key)?;
+
+        for key in &stale {
+            self.clear_deadline(key)?;
+        }
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        let mut meta = self.table_raw_meta(meta_key)?;
+        meta.len = meta.len.saturating_sub(expired.len() as u64);
+
+        let mut batch = Batch::default();
+        for key in &expired {
+            batch.remove(key);
+        }
+
+        if !meta.is_empty() {
+            batch.insert(meta_key, meta.encode().into_raw());
+        } else {
+            batch.remove(meta_key);
+        }
+
+        self.items.apply_batch(batch)?;
+
+        for key in &expired {
+            self.clear_deadline(key)?;
+        }
+        if meta.is_empty() {
+            self.clear_deadline(meta_key)?;
+        }
+
+        Ok(expired.len())
+    }
+
     pub fn table_get(&self, name: &[u8], key: &[u8]) -> Result<Option<IVec>, Error> {
-        self.get_record(&keys::table(name, key))?
+        let key = keys::table(name, key);
+
+        if self.is_expired(&keys::table_meta(name))? || self.is_expired(&key)? {
+            return Ok(None);
+        }
+
+        self.table_get_field(&key)
+    }
+
+    // reads the raw field key `key`, without checking expiry
+    fn table_get_field(&self, key: &[u8]) -> Result<Option<IVec>, Error> {
+        self.get_record(key)?
             .map(|rec| {
                 if rec.tag() != Tag::Table {
                     Err(Error::BadType(Tag::Table, rec.tag()))
@@ -41,19 +139,31 @@ impl Conn {
         let mutex = self.locks.lock(&meta_key);
         let _guard = mutex.write();
 
-        let mut meta = self.table_get_meta(name)?;
-        let old = self
-            .get_record(&key)?
-            .map(|rec| {
-                if rec.tag() != Tag::Table {
-                    Err(Error::BadType(Tag::Table, rec.tag()))
-                } else {
-                    Ok(rec.data())
-                }
-            })
-            .transpose()?;
+        self.table_reap(&meta_key)?;
+        self.table_update_locked(&meta_key, &key, f)
+    }
+
+    // reaps the table at `meta_key` if it expired, or its expired fields otherwise,
+    // the caller must hold the write lock for `meta_key`
+    pub(crate) fn table_reap(&self, meta_key: &[u8]) -> Result<(), Error> {
+        if !self.reap_if_expired(meta_key)? {
+            self.table_reap_fields(meta_key)?;
+        }
+        Ok(())
+    }
+
+    // the caller must hold the write lock for `meta_key`, and have reaped expired fields
+    fn table_update_locked<F: for<'a> FnOnce(&'a Meta, &'a Option<IVec>) -> Option<IVec>>(
+        &self,
+        meta_key: &IVec,
+        key: &IVec,
+        f: F,
+    ) -> Result<Option<IVec>, Error> {
+        let mut meta = self.table_raw_meta(meta_key)?;
+        let old = self.table_get_field(key)?;
 
         let new = f(&meta, &old);
+        let removed = old.is_some() && new.is_none();
 
         match (&old, &new) {
             (None, Some(_)) => {
@@ -68,36 +178,43 @@ impl Conn {
         if cfg!(feature = "safe") {
             let mut batch = Batch::default();
 
-            if meta.len() > 0 {
-                batch.insert(&meta_key, meta.encode().into_raw());
+            if !meta.is_empty() {
+                batch.insert(meta_key, meta.encode().into_raw());
             } else {
                 debug_assert!(new.is_none());
-                batch.remove(&meta_key)
+                batch.remove(meta_key)
             }
 
             if let Some(iv) = new {
-                batch.insert(&key, Record::FromData(Tag::Table, iv).into_raw());
+                batch.insert(key, Record::FromData(Tag::Table, iv).into_raw());
             } else {
-                batch.remove(&key);
+                batch.remove(key);
             }
 
             self.items.apply_batch(batch)?;
         } else {
-            if meta.len() > 0 {
-                self.items.insert(&meta_key, meta.encode().into_raw())?;
+            if !meta.is_empty() {
+                self.items.insert(meta_key, meta.encode().into_raw())?;
             } else {
                 debug_assert!(new.is_none());
-                self.items.remove(&meta_key)?;
+                self.items.remove(meta_key)?;
             }
 
             if let Some(iv) = new {
                 self.items
-                    .insert(&key, Record::FromData(Tag::Table, iv).into_raw())?;
+                    .insert(key, Record::FromData(Tag::Table, iv).into_raw())?;
             } else {
-                self.items.remove(&key)?;
+                self.items.remove(key)?;
             }
         }
 
+        if removed {
+            self.clear_deadline(key)?;
+        }
+        if meta.is_empty() {
+            self.clear_deadline(meta_key)?;
+        }
+
         Ok(old)
     }
 
@@ -108,10 +225,113 @@ impl Conn {
     pub fn table_remove(&self, name: &[u8], key: &[u8]) -> Result<Option<IVec>, Error> {
         self.table_update(name, key, move |_, _| None)
     }
+
+    /// Sets the field `key` of table `name` to `val` only if it doesn't exist yet.
+    ///
+    /// Returns whether the field was set.
+    pub fn table_insert_if_absent(
+        &self,
+        name: &[u8],
+        key: &[u8],
+        val: IVec,
+    ) -> Result<bool, Error> {
+        let old = self.table_update(name, key, move |_, old| old.clone().or(Some(val)))?;
+        Ok(old.is_none())
+    }
+
+    /// Replaces the field `key` of table `name` with `new` only if it currently holds
+    /// `expected`, where `None` stands for a missing field on either side.
+    ///
+    /// Returns whether the swap happened.
+    pub fn table_compare_and_swap(
+        &self,
+        name: &[u8],
+        key: &[u8],
+        expected: Option<&[u8]>,
+        new: Option<IVec>,
+    ) -> Result<bool, Error> {
+        let mut swapped = false;
+
+        self.table_update(name, key, |_, old| {
+            if old.as_deref() == expected {
+                swapped = true;
+                new
+            } else {
+                old.clone()
+            }
+        })?;
+
+        Ok(swapped)
+    }
+
+    /// Sets the field `key` of table `name` to expire at `deadline`.
+    /// A deadline in the past removes the field immediately.
+    ///
+    /// Overwriting the field keeps its expiry, removing it clears the expiry.
+    /// Returns `false` if the field does not exist.
+    pub fn table_expire(
+        &self,
+        name: &[u8],
+        key: &[u8],
+        deadline: SystemTime,
+    ) -> Result<bool, Error> {
+        let meta_key = IVec::from(keys::table_meta(name));
+        let key = IVec::from(keys::table(name, key));
+
+        let mutex = self.locks.lock(&meta_key);
+        let _guard = mutex.write();
+
+        self.table_reap(&meta_key)?;
+
+        if self.get_record(&key)?.is_none() {
+            return Ok(false);
+        }
+
+        let deadline = to_millis(deadline);
+
+        if deadline <= now_millis() {
+            self.table_update_locked(&meta_key, &key, |_, _| None)?;
+        } else {
+            self.set_deadline(&key, deadline)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Remaining time to live of the field `key` of table `name` in milliseconds.
+    ///
+    /// Tells a missing field apart from one that exists without an expiry.
+    pub fn table_field_ttl(&self, name: &[u8], key: &[u8]) -> Result<Ttl, Error> {
+        let key = keys::table(name, key);
+        let deadline = self.raw_deadline(&key)?;
+
+        let now = now_millis();
+        if deadline.is_some_and(|deadline| deadline <= now)
+            || self.is_expired(&keys::table_meta(name))?
+            || !self.items.contains_key(&key)?
+        {
+            return Ok(Ttl::Missing);
+        }
+
+        Ok(match deadline {
+            Some(deadline) => Ttl::Expires(deadline - now),
+            None => Ttl::Persistent,
+        })
+    }
 }
 
 #[derive(Error, Debug)]
 pub enum TableError {
     #[error("invalid table metadata, key was: {0:#?}")]
     InvalidMeta(IVec),
+    #[error("invalid table field key: {0:#?}")]
+    InvalidField(IVec),
+    #[error("table value is not an integer: {0:#?}")]
+    NotAnInteger(IVec),
+    #[error("table value is not a float: {0:#?}")]
+    NotAFloat(IVec),
+    #[error("increment would overflow")]
+    Overflow,
+    #[error("table scan count can't be zero")]
+    ZeroCount,
 }
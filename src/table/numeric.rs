@@ -0,0 +1,84 @@
+use super::*;
+
+// numbers are stored as decimal text, so they read back the same through `table_get`
+
+fn parse_int(val: &[u8]) -> Option<i64> {
+    std::str::from_utf8(val).ok()?.parse().ok()
+}
+
+fn parse_float(val: &[u8]) -> Option<f64> {
+    let float: f64 = std::str::from_utf8(val).ok()?.parse().ok()?;
+    Some(float).filter(|f| f.is_finite())
+}
+
+impl Conn {
+    /// Adds `delta` to the integer stored in the field `key` of table `name`, returning the
+    /// new value. A missing field counts as zero.
+    ///
+    /// Values are stored as decimal text. Fails with [`TableError::NotAnInteger`] if the
+    /// field holds anything else, and [`TableError::Overflow`] if the result doesn't fit in
+    /// an `i64`, leaving the field unchanged.
+    pub fn table_incr_by(&self, name: &[u8], key: &[u8], delta: i64) -> Result<i64, Error> {
+        let mut res = Ok(0);
+
+        self.table_update(name, key, |_, old| {
+            let current = match old {
+                Some(old) => match parse_int(old) {
+                    Some(current) => current,
+                    None => {
+                        res = Err(TableError::NotAnInteger(old.clone()));
+                        return Some(old.clone());
+                    }
+                },
+                None => 0,
+            };
+
+            match current.checked_add(delta) {
+                Some(new) => {
+                    res = Ok(new);
+                    Some(new.to_string().as_bytes().into())
+                }
+                None => {
+                    res = Err(TableError::Overflow);
+                    old.clone()
+                }
+            }
+        })?;
+
+        Ok(res?)
+    }
+
+    /// Adds `delta` to the float stored in the field `key` of table `name`, returning the
+    /// new value. A missing field counts as zero.
+    ///
+    /// Values are stored as decimal text. Fails with [`TableError::NotAFloat`] if the field
+    /// holds anything else, and [`TableError::Overflow`] if the result isn't finite, leaving
+    /// the field unchanged.
+    pub fn table_incr_by_float(&self, name: &[u8], key: &[u8], delta: f64) -> Result<f64, Error> {
+        let mut res = Ok(0.0);
+
+        self.table_update(name, key, |_, old| {
+            let current = match old {
+                Some(old) => match parse_float(old) {
+                    Some(current) => current,
+                    None => {
+                        res = Err(TableError::NotAFloat(old.clone()));
+                        return Some(old.clone());
+                    }
+                },
+                None => 0.0,
+            };
+
+            let new = current + delta;
+            if new.is_finite() {
+                res = Ok(new);
+                Some(new.to_string().as_bytes().into())
+            } else {
+                res = Err(TableError::Overflow);
+                old.clone()
+            }
+        })?;
+
+        Ok(res?)
+    }
+}
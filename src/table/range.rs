@@ -0,0 +1,151 @@
+use super::*;
+use std::ops::{Bound, RangeBounds};
+
+pub type TableRangeIter =
+    Box<dyn DoubleEndedIterator<Item = Result<(Vec<u8>, IVec), Error>> + Send>;
+
+#[derive(Clone, Debug, Default)]
+pub struct TablePage {
+    /// The last field returned, to pass to the next call, `None` once the table is exhausted.
+    pub cursor: Option<Vec<u8>>,
+    /// Unescaped field names and their values.
+    pub fields: Vec<(Vec<u8>, IVec)>,
+}
+
+// splits a raw field key of the table at `meta_key` into its unescaped field and value
+fn decode_field(meta_key: &[u8], key: IVec, raw: IVec) -> Result<(Vec<u8>, IVec), Error> {
+    let field = match take_until_terminator(&key[meta_key.len()..]) {
+        Ok((field, [])) => field.to_vec().unescape(),
+        _ => return Err(TableError::InvalidField(key).into()),
+    };
+
+    let rec = Record::decode(raw)?;
+    if rec.tag() != Tag::Table {
+        Err(Error::BadType(Tag::Table, rec.tag()))
+    } else {
+        Ok((field, rec.data()))
+    }
+}
+
+impl Conn {
+    /// Iterates over every field of `name` and its value, in key order.
+    ///
+    /// Fields are ordered by their escaped bytes, which matches byte order except that a
+    /// field followed by a NUL byte sorts before the field on its own.
+    /// Fields are read lazily, so writes made while iterating may be observed.
+    pub fn table_iter(&self, name: &[u8]) -> Result<TableRangeIter, Error> {
+        self.table_range::<&[u8], _>(name, ..)
+    }
+
+    /// Like [`Conn::table_iter`], but only visits fields within `range`.
+    pub fn table_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        name: &[u8],
+        range: R,
+    ) -> Result<TableRangeIter, Error> {
+        let meta_key = IVec::from(keys::table_meta(name));
+
+        if self.is_expired(&meta_key)? {
+            return Ok(Box::new(std::iter::empty()));
+        }
+
+        let field_bound = |bound: Bound<&K>, unbounded: Bound<Vec<u8>>| match bound {
+            Bound::Included(field) => Bound::Included(keys::table(name, field.as_ref())),
+            Bound::Excluded(field) => Bound::Excluded(keys::table(name, field.as_ref())),
+            Bound::Unbounded => unbounded,
+        };
+        let start = field_bound(range.start_bound(), Bound::Excluded(meta_key.to_vec()));
+        let end = field_bound(
+            range.end_bound(),
+            Bound::Excluded(keyspace::skip_past(&meta_key)),
+        );
+
+        // sled doesn't accept ranges that end before they start
+        let empty = match (&start, &end) {
+            (Bound::Included(start), Bound::Included(end)) => start > end,
+            (Bound::Included(start), Bound::Excluded(end))
+            | (Bound::Excluded(start), Bound::Included(end))
+            | (Bound::Excluded(start), Bound::Excluded(end)) => start >= end,
+            _ => false,
+        };
+        if empty {
+            return Ok(Box::new(std::iter::empty()));
+        }
+
+        Ok(self.table_entries(meta_key, self.items.range::<Vec<u8>, _>((start, end))))
+    }
+
+    /// Iterates over the fields of `name` starting with `prefix` and their values, in key order.
+    pub fn table_scan_prefix(&self, name: &[u8], prefix: &[u8]) -> Result<TableRangeIter, Error> {
+        let meta_key = IVec::from(keys::table_meta(name));
+
+        if self.is_expired(&meta_key)? {
+            return Ok(Box::new(std::iter::empty()));
+        }
+
+        // escaping never splits a byte's encoding across the end of `prefix`, so every
+        // escaped field starting with the escaped prefix unescapes to a field starting with it
+        let mut start = meta_key.to_vec();
+        escape_into(prefix, &mut start);
+
+        Ok(self.table_entries(meta_key, self.items.scan_prefix(start)))
+    }
+
+    // decodes the raw entries `iter` of the table at `meta_key`, skipping expired fields
+    fn table_entries(&self, meta_key: IVec, iter: sled::Iter) -> TableRangeIter {
+        let ttl = self.ttl.clone();
+        let now = now_millis();
+
+        let iter = iter.filter_map(move |entry| {
+            let (key, raw) = match entry {
+                Ok(entry) => entry,
+                Err(e) => return Some(Err(e.into())),
+            };
+
+            // the meta key is only in range for an empty prefix
+            if key == meta_key {
+                return None;
+            }
+
+            match ttl.get(&key) {
+                Ok(Some(deadline)) if decode_deadline(&deadline).is_some_and(|d| d <= now) => None,
+                Err(e) => Some(Err(e.into())),
+                _ => Some(decode_field(&meta_key, key, raw)),
+            }
+        });
+
+        Box::new(iter)
+    }
+
+    /// Returns up to `count` fields of `name` in key order, starting just after the field `after`,
+    /// or from the first field if `after` is `None`.
+    ///
+    /// A full page always returns a cursor, so the last page may be empty.
+    /// A `count` of zero is an error, since its empty page couldn't carry a cursor.
+    pub fn table_scan(
+        &self,
+        name: &[u8],
+        after: Option<&[u8]>,
+        count: usize,
+    ) -> Result<TablePage, Error> {
+        if count == 0 {
+            return Err(TableError::ZeroCount.into());
+        }
+
+        let iter = match after {
+            Some(after) => {
+                self.table_range::<&[u8], _>(name, (Bound::Excluded(after), Bound::Unbounded))?
+            }
+            None => self.table_iter(name)?,
+        };
+
+        let fields = iter.take(count).collect::<Result<Vec<_>, _>>()?;
+        let cursor = if fields.len() == count {
+            fields.last().map(|(field, _)| field.clone())
+        } else {
+            None
+        };
+
+        Ok(TablePage { cursor, fields })
+    }
+}
@@ -0,0 +1,132 @@
+use sledis::*;
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+mod common;
+use common::TempDb;
+
+#[test]
+fn pops_without_waiting_when_a_list_has_elements() {
+    let store = TempDb::new();
+    store
+        .list_push_back(b"second", b"a".as_ref().into())
+        .unwrap();
+    store
+        .list_push_back(b"second", b"b".as_ref().into())
+        .unwrap();
+
+    let names: [&[u8]; 2] = [b"first", b"second"];
+    let (name, val) = store
+        .list_pop_back_blocking(&names, Some(Duration::from_secs(10)))
+        .unwrap()
+        .unwrap();
+    assert_eq!(name, b"second");
+    assert_eq!(val, b"b");
+
+    // timeouts too long to represent don't overflow
+    let (_, val) = store
+        .list_pop_front_blocking(&names, Some(Duration::MAX))
+        .unwrap()
+        .unwrap();
+    assert_eq!(val, b"a");
+}
+
+#[test]
+fn times_out_on_empty_lists() {
+    let store = TempDb::new();
+    let start = Instant::now();
+
+    let res = store
+        .list_pop_front_blocking(&[b"empty"], Some(Duration::from_millis(50)))
+        .unwrap();
+    assert!(res.is_none());
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn wakes_on_push_from_another_connection() {
+    let store = TempDb::new();
+    let other = store.select(databases::DEFAULT).unwrap();
+
+    let waiter = {
+        let conn = Conn::clone(&store);
+        thread::spawn(move || conn.list_pop_front_blocking(&[b"jobs"], None).unwrap())
+    };
+
+    thread::sleep(Duration::from_millis(20));
+    other
+        .list_push_back(b"jobs", b"job".as_ref().into())
+        .unwrap();
+
+    let (name, val) = waiter.join().unwrap().unwrap();
+    assert_eq!(name, b"jobs");
+    assert_eq!(val, b"job");
+}
+
+#[test]
+fn each_element_goes_to_one_waiter() {
+    let store = TempDb::new();
+    let total = 200u32;
+
+    let consumers = (0..4)
+        .map(|_| {
+            let conn = Conn::clone(&store);
+            thread::spawn(move || {
+                let mut got = Vec::new();
+                while let Some((_, val)) = conn
+                    .list_pop_front_blocking(&[b"jobs"], Some(Duration::from_millis(500)))
+                    .unwrap()
+                {
+                    got.push(val.to_vec());
+                }
+                got
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for i in 0..total {
+        store
+            .list_push_back(b"jobs", i.to_be_bytes().to_vec().into())
+            .unwrap();
+    }
+
+    let mut received = consumers
+        .into_iter()
+        .flat_map(|handle| handle.join().unwrap())
+        .collect::<Vec<_>>();
+    received.sort();
+
+    let expected = (0..total)
+        .map(|i| i.to_be_bytes().to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(received, expected);
+}
+
+#[test]
+fn only_wakes_for_the_lists_waited_on() {
+    let store = TempDb::new();
+
+    let waiter = {
+        let conn = Conn::clone(&store);
+        thread::spawn(move || {
+            conn.list_pop_front_blocking(&[b"jobs"], Some(Duration::from_secs(10)))
+                .unwrap()
+        })
+    };
+
+    thread::sleep(Duration::from_millis(20));
+    store
+        .list_push_back(b"other", b"ignored".as_ref().into())
+        .unwrap();
+    thread::sleep(Duration::from_millis(20));
+    store
+        .list_push_back(b"jobs", b"job".as_ref().into())
+        .unwrap();
+
+    let (name, val) = waiter.join().unwrap().unwrap();
+    assert_eq!(name, b"jobs");
+    assert_eq!(val, b"job");
+    assert_eq!(store.list_len(b"other").unwrap(), 1);
+}
@@ -16,6 +16,12 @@ impl TempDb {
     }
 }
 
+impl Default for TempDb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl std::ops::Deref for TempDb {
     type Target = sledis::Conn;
 
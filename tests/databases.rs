@@ -0,0 +1,77 @@
+use sledis::{databases, expiry::Ttl};
+
+mod common;
+use common::TempDb;
+
+#[test]
+fn databases_are_isolated() {
+    let store = TempDb::new();
+    let tenant = store.select(b"tenant").unwrap();
+
+    store.blob_insert(b"key", b"default".into()).unwrap();
+    tenant.list_push_back(b"key", b"tenant".into()).unwrap();
+
+    assert_eq!(store.blob_get(b"key").unwrap().unwrap(), b"default");
+    assert_eq!(tenant.list_get(b"key", 0).unwrap().unwrap(), b"tenant");
+
+    tenant.clear().unwrap();
+    assert_eq!(store.blob_get(b"key").unwrap().unwrap(), b"default");
+
+    let default = tenant.select(databases::DEFAULT).unwrap();
+    assert_eq!(default.blob_get(b"key").unwrap().unwrap(), b"default");
+}
+
+#[test]
+fn swap_db_exchanges_contents() {
+    let store = TempDb::new();
+    let one = store.select(b"1").unwrap();
+
+    store.blob_insert(b"a", b"0".into()).unwrap();
+    store.blob_insert(b"shared", b"0".into()).unwrap();
+    one.table_insert(b"b", b"f", b"1".into()).unwrap();
+    one.blob_insert(b"shared", b"1".into()).unwrap();
+    one.expire(b"b", std::time::Duration::from_secs(100))
+        .unwrap();
+
+    store.swap_db(databases::DEFAULT, b"1").unwrap();
+
+    let default = store.select(databases::DEFAULT).unwrap();
+    assert_eq!(default.key_type(b"a").unwrap(), None);
+    assert_eq!(default.table_get(b"b", b"f").unwrap().unwrap(), b"1");
+    assert_eq!(default.ttl(b"b").unwrap(), Ttl::Expires(100));
+    assert_eq!(default.blob_get(b"shared").unwrap().unwrap(), b"1");
+
+    let one = store.select(b"1").unwrap();
+    assert_eq!(one.blob_get(b"a").unwrap().unwrap(), b"0");
+    assert_eq!(one.key_type(b"b").unwrap(), None);
+    assert_eq!(one.blob_get(b"shared").unwrap().unwrap(), b"0");
+    assert_eq!(one.deadlines.len(), 0);
+
+    // swapping back restores the original mapping
+    store.swap_db(b"1", databases::DEFAULT).unwrap();
+    let default = store.select(databases::DEFAULT).unwrap();
+    assert_eq!(default.blob_get(b"a").unwrap().unwrap(), b"0");
+}
+
+#[test]
+fn connections_keep_their_contents_across_a_swap() {
+    let store = TempDb::new();
+    let one = store.select(b"1").unwrap();
+    let two = store.select(b"2").unwrap();
+
+    one.list_push_back(b"l", b"1".into()).unwrap();
+    two.list_push_back(b"l", b"2".into()).unwrap();
+
+    store.swap_db(b"1", b"2").unwrap();
+
+    // writes through a connection opened before the swap follow its contents
+    one.list_push_back(b"l", b"1".into()).unwrap();
+
+    let now_two = store.select(b"2").unwrap();
+    assert_eq!(now_two.list_len(b"l").unwrap(), 2);
+    assert_eq!(now_two.list_get(b"l", 1).unwrap().unwrap(), b"1");
+
+    let now_one = store.select(b"1").unwrap();
+    assert_eq!(now_one.list_len(b"l").unwrap(), 1);
+    assert_eq!(now_one.list_get(b"l", 0).unwrap().unwrap(), b"2");
+}
@@ -0,0 +1,233 @@
+use std::{
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use sledis::expiry::Ttl;
+
+mod common;
+use common::TempDb;
+
+const SHORT: Duration = Duration::from_millis(20);
+
+#[test]
+fn expire_missing_key() {
+    let store = TempDb::new();
+    assert!(!store.expire(b"missing", SHORT).unwrap());
+    assert_eq!(store.pttl(b"missing").unwrap(), Ttl::Missing);
+    assert!(!store.persist(b"missing").unwrap());
+}
+
+#[test]
+fn expired_keys_are_absent() {
+    let store = TempDb::new();
+
+    store.blob_insert(b"blob", b"val".into()).unwrap();
+    store.list_push_back(b"list", b"val".into()).unwrap();
+    store.table_insert(b"table", b"key", b"val".into()).unwrap();
+
+    for name in &[&b"blob"[..], b"list", b"table"] {
+        assert!(store.expire(name, SHORT).unwrap());
+        assert!(matches!(store.pttl(name).unwrap(), Ttl::Expires(ms) if ms <= 20));
+    }
+
+    thread::sleep(SHORT * 2);
+
+    assert_eq!(store.blob_get(b"blob").unwrap(), None);
+    assert_eq!(store.list_get(b"list", 0).unwrap(), None);
+    assert_eq!(store.list_len(b"list").unwrap(), 0);
+    assert_eq!(store.table_get(b"table", b"key").unwrap(), None);
+    assert_eq!(store.table_get_meta(b"table").unwrap().len(), 0);
+
+    for name in &[&b"blob"[..], b"list", b"table"] {
+        assert_eq!(store.pttl(name).unwrap(), Ttl::Missing);
+    }
+}
+
+#[test]
+fn write_after_expiry_starts_fresh() {
+    let store = TempDb::new();
+
+    store.list_push_back(b"list", b"a".into()).unwrap();
+    store.list_push_back(b"list", b"b".into()).unwrap();
+    store.expire(b"list", SHORT).unwrap();
+    thread::sleep(SHORT * 2);
+
+    store.list_push_back(b"list", b"c".into()).unwrap();
+    assert_eq!(store.list_len(b"list").unwrap(), 1);
+    assert_eq!(store.list_get(b"list", 0).unwrap().unwrap(), b"c");
+    assert_eq!(store.pttl(b"list").unwrap(), Ttl::Persistent);
+}
+
+#[test]
+fn persist_and_overwrite_clear_expiry() {
+    let store = TempDb::new();
+
+    store.blob_insert(b"blob", b"val".into()).unwrap();
+    store.expire(b"blob", Duration::from_secs(100)).unwrap();
+    assert_eq!(store.ttl(b"blob").unwrap(), Ttl::Expires(100));
+    assert!(store.persist(b"blob").unwrap());
+    assert_eq!(store.ttl(b"blob").unwrap(), Ttl::Persistent);
+
+    store.expire(b"blob", Duration::from_secs(100)).unwrap();
+    store.blob_insert(b"blob", b"new".into()).unwrap();
+    assert_eq!(store.ttl(b"blob").unwrap(), Ttl::Persistent);
+    assert_eq!(store.blob_get(b"blob").unwrap().unwrap(), b"new");
+}
+
+#[test]
+fn huge_timeouts_saturate() {
+    let store = TempDb::new();
+
+    store.blob_insert(b"blob", b"val".into()).unwrap();
+    assert!(store.expire(b"blob", Duration::MAX).unwrap());
+    assert!(matches!(store.pttl(b"blob").unwrap(), Ttl::Expires(_)));
+    assert_eq!(store.blob_get(b"blob").unwrap().unwrap(), b"val");
+}
+
+#[test]
+fn expire_at_in_the_past_removes() {
+    let store = TempDb::new();
+
+    store.table_insert(b"table", b"key", b"val".into()).unwrap();
+    assert!(store
+        .expire_at(b"table", SystemTime::now() - SHORT)
+        .unwrap());
+    assert_eq!(store.table_get(b"table", b"key").unwrap(), None);
+    assert_eq!(store.items.len(), 0);
+}
+
+#[test]
+fn reaper_removes_due_keys_in_order() {
+    let store = TempDb::new();
+
+    for i in 0..10u8 {
+        store.list_push_back(&[i], vec![i; 4].into()).unwrap();
+        store.list_push_back(&[i], vec![i; 4].into()).unwrap();
+    }
+
+    for i in 0..5u8 {
+        store.expire(&[i], SHORT).unwrap();
+    }
+    store.expire(&[5], Duration::from_secs(100)).unwrap();
+
+    thread::sleep(SHORT * 2);
+
+    assert_eq!(store.reap_expired(3).unwrap(), 3);
+    assert_eq!(store.reap_expired(100).unwrap(), 2);
+    assert_eq!(store.reap_expired(100).unwrap(), 0);
+
+    // 5 surviving lists with a meta key and two elements each
+    assert_eq!(store.items.len(), 15);
+    assert_eq!(store.ttl.len(), 1);
+    assert_eq!(store.deadlines.len(), 1);
+}
+
+#[test]
+fn reaper_skips_changed_deadlines() {
+    let store = TempDb::new();
+
+    store.blob_insert(b"a", b"val".into()).unwrap();
+    store.blob_insert(b"b", b"val".into()).unwrap();
+    store.expire(b"a", SHORT).unwrap();
+    store.expire(b"b", SHORT).unwrap();
+    store.expire(b"a", Duration::from_secs(100)).unwrap();
+    store.persist(b"b").unwrap();
+
+    thread::sleep(SHORT * 2);
+
+    assert_eq!(store.reap_expired(100).unwrap(), 0);
+    assert!(store.blob_get(b"a").unwrap().is_some());
+    assert!(store.blob_get(b"b").unwrap().is_some());
+    assert_eq!(store.deadlines.len(), 1);
+}
+
+#[test]
+fn table_fields_expire_independently() {
+    let store = TempDb::new();
+
+    store.table_insert(b"flags", b"a", b"1".into()).unwrap();
+    store.table_insert(b"flags", b"b", b"1".into()).unwrap();
+    store.table_insert(b"flags", b"c", b"1".into()).unwrap();
+
+    let soon = SystemTime::now() + SHORT;
+    assert!(store.table_expire(b"flags", b"a", soon).unwrap());
+    assert!(store.table_expire(b"flags", b"b", soon).unwrap());
+    assert!(!store.table_expire(b"flags", b"missing", soon).unwrap());
+    assert!(matches!(
+        store.table_field_ttl(b"flags", b"a").unwrap(),
+        Ttl::Expires(ms) if ms <= 20
+    ));
+    assert_eq!(
+        store.table_field_ttl(b"flags", b"c").unwrap(),
+        Ttl::Persistent
+    );
+
+    thread::sleep(SHORT * 2);
+
+    assert_eq!(store.table_get(b"flags", b"a").unwrap(), None);
+    assert_eq!(store.table_field_ttl(b"flags", b"a").unwrap(), Ttl::Missing);
+    assert_eq!(store.table_get_meta(b"flags").unwrap().len(), 1);
+
+    let seen = store
+        .table_update(b"flags", b"b", |meta, old| {
+            assert_eq!(meta.len(), 1);
+            assert_eq!(old, &None);
+            Some(b"2".into())
+        })
+        .unwrap();
+    assert_eq!(seen, None);
+    assert_eq!(store.table_get_meta(b"flags").unwrap().len(), 2);
+    assert_eq!(
+        store.table_field_ttl(b"flags", b"b").unwrap(),
+        Ttl::Persistent
+    );
+}
+
+#[test]
+fn reaper_removes_table_fields() {
+    let store = TempDb::new();
+
+    store.table_insert(b"flags", b"a", b"1".into()).unwrap();
+    store.table_insert(b"flags", b"b", b"1".into()).unwrap();
+    store
+        .table_expire(b"flags", b"a", SystemTime::now() + SHORT)
+        .unwrap();
+
+    thread::sleep(SHORT * 2);
+
+    assert_eq!(store.reap_expired(100).unwrap(), 1);
+    assert_eq!(store.table_get_meta(b"flags").unwrap().len(), 1);
+    assert_eq!(store.items.len(), 2);
+    assert_eq!(store.ttl.len(), 0);
+    assert_eq!(store.deadlines.len(), 0);
+}
+
+#[test]
+fn stale_field_deadlines_are_ignored() {
+    let store = TempDb::new();
+
+    // a deadline left behind by a field that's already gone, as after a crash between
+    // removing the field and clearing its deadline
+    let stale = |name: &[u8]| {
+        store
+            .ttl
+            .insert(
+                sledis::keys::table(name, b"gone"),
+                &sledis::expiry::encode_deadline(1),
+            )
+            .unwrap();
+    };
+
+    stale(b"flags");
+    stale(b"missing");
+
+    store.table_insert(b"flags", b"a", b"1".into()).unwrap();
+    assert_eq!(store.table_get_meta(b"flags").unwrap().len(), 1);
+    assert_eq!(store.table_get_meta(b"missing").unwrap().len(), 0);
+
+    // writing reaps the table, clearing its stale deadline
+    store.table_insert(b"flags", b"b", b"1".into()).unwrap();
+    assert_eq!(store.table_get_meta(b"flags").unwrap().len(), 2);
+    assert_eq!(store.ttl.len(), 1);
+}
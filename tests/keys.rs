@@ -61,3 +61,58 @@ impl OwnedKey {
 fn encode_inj((k1, k2): (OwnedKey, OwnedKey)) -> bool {
     (k1 != k2) || (k1.encode() == k2.encode())
 }
+
+impl OwnedKey {
+    fn tag(&self) -> record::Tag {
+        match self {
+            Blob(_) => record::Tag::Blob,
+            List(..) | ListMeta(_) => record::Tag::List,
+            Table(..) | TableMeta(_) => record::Tag::Table,
+        }
+    }
+
+    fn parsed(&self) -> ParsedKey {
+        let (name, element) = match self {
+            Blob(name) | ListMeta(name) | TableMeta(name) => (name, Element::Bare),
+            List(name, ix) => (name, Element::List(*ix)),
+            Table(name, key) => (name, Element::Table(key.clone())),
+        };
+        ParsedKey {
+            name: name.clone(),
+            element,
+        }
+    }
+}
+
+#[quickcheck]
+fn parse_tagged_inverts_encode(k: OwnedKey) -> bool {
+    keys::parse_tagged(&k.encode(), k.tag()) == Some(k.parsed())
+}
+
+#[quickcheck]
+fn parse_inverts_encode(k: OwnedKey) -> bool {
+    let parsed = keys::parse(&k.encode()).expect("failed to parse");
+    let expected = k.parsed();
+
+    parsed.name == expected.name
+        && match (parsed.element, expected.element) {
+            (Element::Ambiguous(ix, _), Element::List(expected)) => ix == expected,
+            (Element::Ambiguous(_, field), Element::Table(expected)) => field == expected,
+            (parsed, expected) => parsed == expected,
+        }
+}
+
+#[test]
+fn parse_ambiguous_index() {
+    // encodes as `7f ff .. ff 00 ff`, which is also a terminated field
+    let ix = -0xff01;
+    let raw = keys::list(b"feed", ix);
+    assert!(matches!(
+        keys::parse(&raw).unwrap().element,
+        Element::Ambiguous(found, _) if found == ix
+    ));
+    assert_eq!(
+        keys::parse_tagged(&raw, record::Tag::List).unwrap().element,
+        Element::List(ix)
+    );
+}
@@ -0,0 +1,258 @@
+use quickcheck_macros::*;
+use sledis::{expiry::Ttl, glob, record::Tag, Error};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime},
+};
+
+mod common;
+use common::TempDb;
+
+fn populate(store: &TempDb, names: &[(Vec<u8>, u8)]) -> BTreeMap<Vec<u8>, Tag> {
+    let mut model = BTreeMap::new();
+
+    for (name, kind) in names {
+        store.remove_item(name).unwrap();
+        let tag = match kind % 3 {
+            0 => {
+                store.blob_insert(name, name.as_slice().into()).unwrap();
+                Tag::Blob
+            }
+            1 => {
+                store.list_push_back(name, name.as_slice().into()).unwrap();
+                store.list_push_back(name, name.as_slice().into()).unwrap();
+                Tag::List
+            }
+            _ => {
+                store
+                    .table_insert(name, name, name.as_slice().into())
+                    .unwrap();
+                store
+                    .table_insert(name, b"", name.as_slice().into())
+                    .unwrap();
+                Tag::Table
+            }
+        };
+        model.insert(name.clone(), tag);
+    }
+
+    model
+}
+
+#[quickcheck]
+fn scan_yields_each_key_once((names, count): (Vec<(Vec<u8>, u8)>, u8)) -> bool {
+    let store = TempDb::new();
+    let model = populate(&store, &names);
+
+    let mut found = BTreeMap::new();
+    let mut cursor = None;
+    loop {
+        let page = store
+            .scan(cursor.as_deref(), count as usize % 4 + 1)
+            .unwrap();
+        for (name, tag) in page.keys {
+            assert!(found.insert(name, tag).is_none());
+        }
+        match page.cursor {
+            Some(next) => cursor = Some(next.to_vec()),
+            None => break,
+        }
+    }
+
+    found == model
+}
+
+#[quickcheck]
+fn scan_match_agrees_with_glob((names, prefix): (Vec<(Vec<u8>, u8)>, Vec<u8>)) -> bool {
+    let store = TempDb::new();
+    let model = populate(&store, &names);
+
+    let mut pattern = prefix.clone();
+    pattern.push(b'*');
+    let pattern = glob::Pattern::new(&pattern);
+
+    let mut found = store.keys(&prefix_pattern(&prefix)).unwrap();
+    found.sort();
+
+    let expected = model
+        .keys()
+        .filter(|name| name.starts_with(&prefix) && pattern.matches(name))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    found == expected
+}
+
+// escapes glob metacharacters in `prefix` and appends a star
+fn prefix_pattern(prefix: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for byt in prefix {
+        if b"*?[]\\".contains(byt) {
+            out.push(b'\\');
+        }
+        out.push(*byt);
+    }
+    out.push(b'*');
+    out
+}
+
+#[test]
+fn glob_patterns() {
+    let cases: &[(&[u8], &[u8], bool)] = &[
+        (b"user:*:session", b"user:42:session", true),
+        (b"user:*:session", b"user:42:sessions", false),
+        (b"job:[0-9]?", b"job:7a", true),
+        (b"job:[0-9]?", b"job:a7", false),
+        (b"job:[^0-9]", b"job:a", true),
+        (b"h\\*llo", b"h*llo", true),
+        (b"h\\*llo", b"hello", false),
+        (b"*", b"", true),
+        (b"a*b*c", b"abxbc", true),
+        (b"a*b*c", b"abxbd", false),
+        (b"[abc", b"[abc", true),
+    ];
+
+    for (pattern, input, expected) in cases {
+        assert_eq!(glob::matches(pattern, input), *expected);
+    }
+
+    assert_eq!(glob::Pattern::new(b"user:*").literal_prefix(), b"user:");
+    assert_eq!(glob::Pattern::new(b"a\\*b?").literal_prefix(), b"a*b");
+}
+
+#[test]
+fn scan_match_pages() {
+    let store = TempDb::new();
+    for i in 0..20u8 {
+        store.blob_insert(&[b'a', i], vec![i].into()).unwrap();
+        store.blob_insert(&[b'b', i], vec![i].into()).unwrap();
+    }
+
+    let mut found = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = store.scan_match(cursor.as_deref(), 3, b"b*").unwrap();
+        found.extend(page.keys.into_iter().map(|(name, _)| name));
+        match page.cursor {
+            Some(next) => cursor = Some(next.to_vec()),
+            None => break,
+        }
+    }
+
+    assert_eq!(found, (0..20u8).map(|i| vec![b'b', i]).collect::<Vec<_>>());
+}
+
+#[test]
+fn scan_rejects_zero_count() {
+    let store = TempDb::new();
+    store.blob_insert(b"a", b"1".into()).unwrap();
+
+    assert!(matches!(store.scan(None, 0), Err(Error::ZeroCount)));
+    assert!(matches!(
+        store.scan_match(None, 0, b"a*"),
+        Err(Error::ZeroCount)
+    ));
+}
+
+#[quickcheck]
+fn key_type_agrees_with_scan(names: Vec<(Vec<u8>, u8)>) -> bool {
+    let store = TempDb::new();
+    let model = populate(&store, &names);
+
+    let all = model.keys().map(Vec::as_slice).collect::<Vec<_>>();
+
+    model
+        .iter()
+        .all(|(name, tag)| store.key_type(name).unwrap() == Some(*tag))
+        && store.exists(&all).unwrap() == model.len()
+}
+
+#[test]
+fn exists_counts_repeats() {
+    let store = TempDb::new();
+    store.blob_insert(b"a", b"1".into()).unwrap();
+    store.list_push_back(b"b", b"1".into()).unwrap();
+
+    assert_eq!(store.exists(&[b"a", b"b", b"c", b"a"]).unwrap(), 3);
+    assert_eq!(store.key_type(b"c").unwrap(), None);
+
+    store
+        .expire(b"a", std::time::Duration::from_millis(0))
+        .unwrap();
+    assert_eq!(store.key_type(b"a").unwrap(), None);
+    assert_eq!(store.exists(&[b"a", b"b"]).unwrap(), 1);
+}
+
+#[test]
+fn rename_moves_collections() {
+    let store = TempDb::new();
+
+    store.list_push_back(b"src", b"a".into()).unwrap();
+    store.list_push_back(b"src", b"b".into()).unwrap();
+    store.table_insert(b"dst", b"field", b"old".into()).unwrap();
+    store.expire(b"src", Duration::from_secs(100)).unwrap();
+
+    assert!(store.rename(b"src", b"dst").unwrap());
+
+    assert_eq!(store.key_type(b"src").unwrap(), None);
+    assert_eq!(store.key_type(b"dst").unwrap(), Some(Tag::List));
+    assert_eq!(store.list_len(b"dst").unwrap(), 2);
+    assert_eq!(store.list_get(b"dst", 1).unwrap().unwrap(), b"b");
+    assert_eq!(store.ttl(b"dst").unwrap(), Ttl::Expires(100));
+    assert_eq!(store.ttl(b"src").unwrap(), Ttl::Missing);
+    assert_eq!(store.items.len(), 3);
+    assert_eq!(store.deadlines.len(), 1);
+
+    assert!(!store.rename(b"src", b"dst").unwrap());
+}
+
+#[test]
+fn rename_nx_keeps_destination() {
+    let store = TempDb::new();
+
+    store.blob_insert(b"a", b"1".into()).unwrap();
+    store.table_insert(b"b", b"f", b"2".into()).unwrap();
+    store
+        .table_expire(b"b", b"f", SystemTime::now() + Duration::from_secs(100))
+        .unwrap();
+
+    assert!(!store.rename_nx(b"a", b"b").unwrap());
+    assert_eq!(store.blob_get(b"a").unwrap().unwrap(), b"1");
+
+    assert!(store.rename_nx(b"b", b"c").unwrap());
+    assert_eq!(store.table_get(b"c", b"f").unwrap().unwrap(), b"2");
+    assert!(matches!(
+        store.table_field_ttl(b"c", b"f").unwrap(),
+        Ttl::Expires(_)
+    ));
+    assert_eq!(store.table_field_ttl(b"b", b"f").unwrap(), Ttl::Missing);
+}
+
+#[test]
+fn copy_is_deep() {
+    let store = TempDb::new();
+
+    store.list_push_back(b"queue", b"a".into()).unwrap();
+    store.list_push_front(b"queue", b"b".into()).unwrap();
+    store.expire(b"queue", Duration::from_secs(100)).unwrap();
+
+    assert!(store.copy(b"queue", b"snapshot", false).unwrap());
+    assert_eq!(
+        store.list_get_meta(b"snapshot").unwrap(),
+        store.list_get_meta(b"queue").unwrap()
+    );
+    assert_eq!(store.ttl(b"snapshot").unwrap(), Ttl::Expires(100));
+
+    store.list_pop_front(b"queue").unwrap();
+    assert_eq!(store.list_len(b"snapshot").unwrap(), 2);
+    assert_eq!(store.list_get(b"snapshot", 0).unwrap().unwrap(), b"b");
+
+    store.blob_insert(b"other", b"x".into()).unwrap();
+    assert!(!store.copy(b"queue", b"other", false).unwrap());
+    assert_eq!(store.key_type(b"other").unwrap(), Some(Tag::Blob));
+    assert!(store.copy(b"queue", b"other", true).unwrap());
+    assert_eq!(store.list_len(b"other").unwrap(), 1);
+
+    assert!(!store.copy(b"queue", b"queue", true).unwrap());
+    assert!(!store.copy(b"missing", b"dst", true).unwrap());
+}
@@ -1,6 +1,6 @@
 use quickcheck::{Arbitrary, Gen};
 use quickcheck_macros::*;
-use sledis::*;
+use sledis::{list::ListEnd, *};
 use std::collections::VecDeque;
 
 mod common;
@@ -123,3 +123,767 @@ fn chaos_test_list_no_name(ops: Vec<DequeuOp>) -> bool {
 
     ops_corr && res_eq
 }
+
+// the elements of `deque` from `start` to `stop` inclusive, with redis semantics
+fn model_range(deque: &VecDeque<Vec<u8>>, start: i64, stop: i64) -> Vec<Vec<u8>> {
+    let len = deque.len() as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        len + stop
+    } else {
+        stop.min(len - 1)
+    };
+
+    if start > stop || start >= len {
+        Vec::new()
+    } else {
+        deque
+            .range(start as usize..=stop as usize)
+            .cloned()
+            .collect()
+    }
+}
+
+fn build(store: &Conn, name: &[u8], ops: &[DequeuOp]) -> VecDeque<Vec<u8>> {
+    let mut deque = VecDeque::new();
+
+    for op in ops {
+        match op {
+            DequeuOp::PushFront(val) => {
+                deque.push_front(val.clone());
+                store.list_push_front(name, val.as_slice().into()).unwrap();
+            }
+            DequeuOp::PushBack(val) => {
+                deque.push_back(val.clone());
+                store.list_push_back(name, val.as_slice().into()).unwrap();
+            }
+            DequeuOp::PopFront => {
+                deque.pop_front();
+                store.list_pop_front(name).unwrap();
+            }
+            DequeuOp::PopBack => {
+                deque.pop_back();
+                store.list_pop_back(name).unwrap();
+            }
+        }
+    }
+
+    deque
+}
+
+#[quickcheck]
+fn range_matches_model((ops, start, stop): (Vec<DequeuOp>, i8, i8)) -> bool {
+    let name = b"range";
+    let store = TempDb::new();
+    let deque = build(&store, name, &ops);
+    let expected = model_range(&deque, start as i64, stop as i64);
+
+    let forward = store
+        .list_range(name, start as i64, stop as i64)
+        .unwrap()
+        .map(|val| val.unwrap().to_vec())
+        .collect::<Vec<_>>();
+
+    let mut backward = store
+        .list_range(name, start as i64, stop as i64)
+        .unwrap()
+        .rev()
+        .map(|val| val.unwrap().to_vec())
+        .collect::<Vec<_>>();
+    backward.reverse();
+
+    forward == expected && backward == expected
+}
+
+#[test]
+fn long_ranges_iterate_from_both_ends() {
+    let name = b"range";
+    let store = TempDb::new();
+
+    // enough mixed pushes and pops to spread the list over several tree nodes,
+    // with negative indices on the front
+    let mut rng = 0x3c6e_f363_u32;
+    let ops = (0..600)
+        .map(|i| {
+            rng ^= rng << 13;
+            rng ^= rng >> 17;
+            rng ^= rng << 5;
+            let val = format!("{:04}", i).into_bytes();
+            match rng % 7 {
+                0..=2 => DequeuOp::PushFront(val),
+                3..=5 => DequeuOp::PushBack(val),
+                _ if rng & 1 == 0 => DequeuOp::PopFront,
+                _ => DequeuOp::PopBack,
+            }
+        })
+        .collect::<Vec<_>>();
+    let deque = build(&store, name, &ops);
+    assert!(deque.len() > 300);
+
+    let backward = store
+        .list_range(name, 0, -1)
+        .unwrap()
+        .rev()
+        .map(|val| val.unwrap().to_vec())
+        .collect::<Vec<_>>();
+    assert!(backward.iter().eq(deque.iter().rev()));
+
+    // both ends meet in the middle without repeating an element
+    let mut iter = store.list_range(name, 0, -1).unwrap();
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+    while let Some(val) = iter.next() {
+        front.push(val.unwrap().to_vec());
+        if let Some(val) = iter.next_back() {
+            back.push(val.unwrap().to_vec());
+        }
+    }
+    back.reverse();
+    front.extend(back);
+    assert!(front.iter().eq(deque.iter()));
+}
+
+#[quickcheck]
+fn trim_matches_model((ops, start, stop): (Vec<DequeuOp>, i8, i8)) -> bool {
+    let name = b"trim";
+    let store = TempDb::new();
+    let deque = build(&store, name, &ops);
+
+    store.list_trim(name, start as i64, stop as i64).unwrap();
+    let expected = model_range(&deque, start as i64, stop as i64)
+        .into_iter()
+        .collect::<VecDeque<_>>();
+
+    // trimmed elements must be gone from the tree, not just hidden by the metadata
+    let stored = store.items.len() - if expected.is_empty() { 0 } else { 1 };
+
+    deep_eq(&store, name, &expected) && stored == expected.len()
+}
+
+#[quickcheck]
+fn remove_matches_model((vals, count, target): (Vec<u8>, i8, u8)) -> bool {
+    let name = b"remove";
+    let store = TempDb::new();
+
+    // a small alphabet so there are plenty of matches
+    let mut deque = vals.iter().map(|v| vec![v % 4]).collect::<VecDeque<_>>();
+    for val in &deque {
+        store.list_push_back(name, val.as_slice().into()).unwrap();
+    }
+    let target = vec![target % 4];
+
+    let limit = if count == 0 {
+        usize::MAX
+    } else {
+        count.unsigned_abs() as usize
+    };
+    let mut matches = (0..deque.len())
+        .filter(|i| deque[*i] == target)
+        .collect::<Vec<_>>();
+    if count < 0 {
+        matches.reverse();
+    }
+    matches.truncate(limit);
+    matches.sort_unstable();
+    for pos in matches.iter().rev() {
+        deque.remove(*pos);
+    }
+
+    let removed = store.list_remove(name, count as i64, &target).unwrap();
+    let stored = store.items.len() - if deque.is_empty() { 0 } else { 1 };
+
+    removed == matches.len() as u64 && deep_eq(&store, name, &deque) && stored == deque.len()
+}
+
+#[quickcheck]
+fn insert_matches_model(ops: Vec<(u8, u8, u8)>) -> bool {
+    let name = b"insert";
+    let store = TempDb::new();
+    let mut deque = VecDeque::new();
+
+    for (kind, pos, val) in ops {
+        let val = vec![val % 8];
+        match kind % 4 {
+            0 => {
+                let pos = pos as usize % (deque.len() + 2);
+                let len = store
+                    .list_insert_at(name, pos as u64, val.as_slice().into())
+                    .unwrap();
+                if pos > deque.len() {
+                    if len.is_some() {
+                        return false;
+                    }
+                } else {
+                    deque.insert(pos, val);
+                    if len != Some(deque.len() as u64) {
+                        return false;
+                    }
+                }
+            }
+            1 => {
+                let before = pos % 2 == 0;
+                let pivot = vec![pos % 8];
+                let len = store
+                    .list_insert(name, before, &pivot, val.as_slice().into())
+                    .unwrap();
+                match deque.iter().position(|v| *v == pivot) {
+                    Some(at) => {
+                        deque.insert(if before { at } else { at + 1 }, val);
+                        if len != Some(deque.len() as u64) {
+                            return false;
+                        }
+                    }
+                    None if len.is_some() => return false,
+                    None => {}
+                }
+            }
+            2 => {
+                if !pop_eq(deque.pop_front(), store.list_pop_front(name).unwrap()) {
+                    return false;
+                }
+            }
+            _ => {
+                if !pop_eq(deque.pop_back(), store.list_pop_back(name).unwrap()) {
+                    return false;
+                }
+            }
+        }
+    }
+
+    let ranged = store
+        .list_range(name, 0, -1)
+        .unwrap()
+        .map(|v| v.unwrap().to_vec())
+        .collect::<Vec<_>>();
+    let stored = store.items.len() - if deque.is_empty() { 0 } else { 1 };
+
+    deep_eq(&store, name, &deque) && ranged.iter().eq(deque.iter()) && stored == deque.len()
+}
+
+#[test]
+fn repeated_inserts_rebalance() {
+    let name = b"rebalance";
+    let store = TempDb::new();
+    let mut deque = VecDeque::new();
+
+    for val in [b"first", b"last "] {
+        deque.push_back(val.to_vec());
+        store.list_push_back(name, val.as_ref().into()).unwrap();
+    }
+
+    // every insert halves the same gap, which runs out well before 200 inserts
+    for i in 0..200u32 {
+        let val = i.to_be_bytes().to_vec();
+        deque.insert(1, val.clone());
+        store.list_insert_at(name, 1, val.into()).unwrap();
+
+        let val = (i + 1000).to_be_bytes().to_vec();
+        deque.insert(deque.len() - 1, val.clone());
+        store.list_insert(name, true, b"last ", val.into()).unwrap();
+    }
+
+    assert!(deep_eq(&store, name, &deque));
+    assert_eq!(store.items.len(), deque.len() + 1);
+
+    let mut trimmed = deque.clone();
+    trimmed.drain(..10);
+    trimmed.truncate(trimmed.len() - 10);
+    store.list_trim(name, 10, -11).unwrap();
+    assert!(deep_eq(&store, name, &trimmed));
+    assert_eq!(store.list_pop_front(name).unwrap().unwrap(), trimmed[0]);
+}
+
+fn model_end(end: bool) -> ListEnd {
+    if end {
+        ListEnd::Back
+    } else {
+        ListEnd::Front
+    }
+}
+
+// which list to move from and to, and whether to pop and push at the back
+type Move = (bool, bool, bool, bool);
+
+#[quickcheck]
+fn move_matches_model((vals, moves): (Vec<u8>, Vec<Move>)) -> bool {
+    let names: [&[u8]; 2] = [b"pending", b"processing"];
+    let store = TempDb::new();
+    let mut deques = [VecDeque::new(), VecDeque::new()];
+
+    for val in vals {
+        deques[0].push_back(vec![val]);
+        store.list_push_back(names[0], vec![val].into()).unwrap();
+    }
+
+    for (from, to, from_back, to_back) in moves {
+        let (from, to) = (from as usize, to as usize);
+
+        let expected = if from_back {
+            deques[from].pop_back()
+        } else {
+            deques[from].pop_front()
+        };
+        if let Some(val) = expected.clone() {
+            if to_back {
+                deques[to].push_back(val);
+            } else {
+                deques[to].push_front(val);
+            }
+        }
+
+        let moved = store
+            .list_move(
+                names[from],
+                names[to],
+                model_end(from_back),
+                model_end(to_back),
+            )
+            .unwrap();
+        if !pop_eq(expected, moved) {
+            return false;
+        }
+    }
+
+    let metas = deques.iter().filter(|deque| !deque.is_empty()).count();
+    deep_eq(&store, names[0], &deques[0])
+        && deep_eq(&store, names[1], &deques[1])
+        && store.items.len() == deques[0].len() + deques[1].len() + metas
+}
+
+#[test]
+fn concurrent_moves_keep_every_element() {
+    let store = TempDb::new();
+    for i in 0..100u8 {
+        store.list_push_back(b"a", vec![i].into()).unwrap();
+    }
+
+    // moving in opposite directions takes the same pair of locks in opposite orders
+    let handles = [(b"a", b"b"), (b"b", b"a")]
+        .iter()
+        .map(|&(src, dst)| {
+            let conn = Conn::clone(&store);
+            std::thread::spawn(move || {
+                for _ in 0..500 {
+                    conn.list_move(src, dst, ListEnd::Front, ListEnd::Back)
+                        .unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let mut seen = store
+        .list_range(b"a", 0, -1)
+        .unwrap()
+        .chain(store.list_range(b"b", 0, -1).unwrap())
+        .map(|val| val.unwrap()[0])
+        .collect::<Vec<_>>();
+    seen.sort_unstable();
+    assert_eq!(seen, (0..100).collect::<Vec<_>>());
+}
+
+#[quickcheck]
+fn position_matches_model((vals, target, rank, count, maxlen): (Vec<u8>, u8, i8, u8, u8)) -> bool {
+    let name = b"position";
+    let store = TempDb::new();
+
+    let deque = vals.iter().map(|v| vec![v % 4]).collect::<VecDeque<_>>();
+    for val in &deque {
+        store.list_push_back(name, val.as_slice().into()).unwrap();
+    }
+    let target = vec![target % 4];
+
+    let found = store.list_position(name, &target, rank as i64, count as u64, maxlen as u64);
+    if rank == 0 {
+        return found.is_err();
+    }
+
+    let mut positions = (0..deque.len()).collect::<Vec<_>>();
+    if rank < 0 {
+        positions.reverse();
+    }
+    if maxlen != 0 {
+        positions.truncate(maxlen as usize);
+    }
+    let mut expected = positions
+        .into_iter()
+        .filter(|i| deque[*i] == target)
+        .skip(rank.unsigned_abs() as usize - 1)
+        .map(|i| i as u64)
+        .collect::<Vec<_>>();
+    if count != 0 {
+        expected.truncate(count as usize);
+    }
+
+    found.unwrap() == expected
+}
+
+#[quickcheck]
+fn capped_pushes_match_model((cap, ops): (u8, Vec<DequeuOp>)) -> bool {
+    let name = b"capped";
+    let store = TempDb::new();
+    let cap = cap as usize % 8 + 1;
+
+    // the cap lives in the list metadata, so the list has to exist first
+    store.list_push_back(name, b"seed".into()).unwrap();
+    store
+        .list_set_cap(name, std::num::NonZeroU64::new(cap as u64))
+        .unwrap();
+    let mut deque = VecDeque::from(vec![b"seed".to_vec()]);
+
+    for op in ops {
+        match op {
+            DequeuOp::PushFront(val) => {
+                deque.push_front(val.clone());
+                deque.truncate(cap);
+                store.list_push_front(name, val.into()).unwrap();
+            }
+            DequeuOp::PushBack(val) => {
+                deque.push_back(val.clone());
+                if deque.len() > cap {
+                    deque.pop_front();
+                }
+                store.list_push_back(name, val.into()).unwrap();
+            }
+            // popping the last element would drop the cap along with the list
+            DequeuOp::PopFront if deque.len() > 1 => {
+                deque.pop_front();
+                store.list_pop_front(name).unwrap();
+            }
+            DequeuOp::PopBack if deque.len() > 1 => {
+                deque.pop_back();
+                store.list_pop_back(name).unwrap();
+            }
+            _ => {}
+        }
+    }
+
+    deep_eq(&store, name, &deque) && store.items.len() == deque.len() + 1
+}
+
+#[quickcheck]
+fn capped_pushes_and_inserts_match_model((cap, ops): (u8, Vec<(u8, u8, u8)>)) -> bool {
+    let name = b"capped";
+    let store = TempDb::new();
+    let cap = cap as usize % 8 + 1;
+    let nz_cap = std::num::NonZeroU64::new(cap as u64).unwrap();
+
+    let mut deque = VecDeque::new();
+    // the cap is dropped along with the list when it's emptied
+    let mut capped = false;
+
+    for (kind, val, pos) in ops {
+        let val = vec![val];
+        match kind % 5 {
+            0 => {
+                deque.push_front(val.clone());
+                deque.truncate(cap);
+                capped = true;
+                store
+                    .list_push_front_capped(name, val.into(), nz_cap)
+                    .unwrap();
+            }
+            1 => {
+                deque.push_back(val.clone());
+                while deque.len() > cap {
+                    deque.pop_front();
+                }
+                capped = true;
+                store
+                    .list_push_back_capped(name, val.into(), nz_cap)
+                    .unwrap();
+            }
+            2 => {
+                let pos = pos as usize % (deque.len() + 1);
+                deque.insert(pos, val.clone());
+                if capped && deque.len() > cap {
+                    if pos == 0 {
+                        deque.pop_back();
+                    } else {
+                        deque.pop_front();
+                    }
+                }
+                let len = store.list_insert_at(name, pos as u64, val.into()).unwrap();
+                if len != Some(deque.len() as u64) {
+                    return false;
+                }
+            }
+            3 => {
+                if store.list_pop_front(name).unwrap().map(|v| v.to_vec()) != deque.pop_front() {
+                    return false;
+                }
+            }
+            4 => {
+                if store.list_pop_back(name).unwrap().map(|v| v.to_vec()) != deque.pop_back() {
+                    return false;
+                }
+            }
+            _ => unreachable!(),
+        }
+
+        if deque.is_empty() {
+            capped = false;
+        }
+    }
+
+    store.list_len(name).unwrap() == deque.len() as u64
+        && deep_eq(&store, name, &deque)
+        && store.items.len() == deque.len() + !deque.is_empty() as usize
+}
+
+#[test]
+fn setting_a_cap_evicts_from_the_front() {
+    let name = b"log";
+    let store = TempDb::new();
+    assert!(!store
+        .list_set_cap(name, std::num::NonZeroU64::new(2))
+        .unwrap());
+
+    for i in 0..5u8 {
+        store.list_push_back(name, vec![i].into()).unwrap();
+    }
+    assert!(store
+        .list_set_cap(name, std::num::NonZeroU64::new(2))
+        .unwrap());
+
+    let vals = store
+        .list_range(name, 0, -1)
+        .unwrap()
+        .map(|val| val.unwrap().to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(vals, vec![vec![3], vec![4]]);
+    assert_eq!(store.list_get_meta(name).unwrap().cap(), Some(2));
+
+    // moving onto a full list evicts like a push
+    store.list_push_back(b"src", vec![9].into()).unwrap();
+    store
+        .list_move(b"src", name, ListEnd::Front, ListEnd::Front)
+        .unwrap();
+    let vals = store
+        .list_range(name, 0, -1)
+        .unwrap()
+        .map(|val| val.unwrap().to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(vals, vec![vec![9], vec![3]]);
+
+    assert!(store.list_set_cap(name, None).unwrap());
+    store.list_push_back(name, vec![10].into()).unwrap();
+    assert_eq!(store.list_len(name).unwrap(), 3);
+}
+
+#[quickcheck]
+fn many_ops_match_model((cap, ops): (u8, Vec<(u8, Vec<u8>)>)) -> bool {
+    let name = b"many";
+    let store = TempDb::new();
+    let mut deque = VecDeque::new();
+
+    // an even `cap` leaves the list uncapped
+    let cap = if cap % 2 == 0 {
+        None
+    } else {
+        Some(cap as usize % 8 + 1)
+    };
+    let seed = b"seed".to_vec();
+    store.list_push_back(name, seed.as_slice().into()).unwrap();
+    deque.push_back(seed);
+    if let Some(cap) = cap {
+        store
+            .list_set_cap(name, std::num::NonZeroU64::new(cap as u64))
+            .unwrap();
+    }
+
+    for (kind, vals) in ops {
+        let vals = vals.into_iter().map(|v| vec![v]).collect::<Vec<_>>();
+        let ivecs = vals.iter().map(|v| v.as_slice().into());
+        match kind % 4 {
+            0 => {
+                for val in &vals {
+                    deque.push_front(val.clone());
+                    if let Some(cap) = cap {
+                        deque.truncate(cap);
+                    }
+                }
+                let len = store.list_push_front_many(name, ivecs).unwrap();
+                if len != deque.len() as u64 {
+                    return false;
+                }
+            }
+            1 => {
+                for val in &vals {
+                    deque.push_back(val.clone());
+                    if cap.is_some_and(|cap| deque.len() > cap) {
+                        deque.pop_front();
+                    }
+                }
+                let len = store.list_push_back_many(name, ivecs).unwrap();
+                if len != deque.len() as u64 {
+                    return false;
+                }
+            }
+            // always leave an element behind, so the cap isn't dropped with the list
+            2 => {
+                let count = vals.len().min(deque.len().saturating_sub(1));
+                let expected = deque.drain(..count).collect::<Vec<_>>();
+                let popped = store.list_pop_front_many(name, count as u64).unwrap();
+                if popped != expected {
+                    return false;
+                }
+            }
+            _ => {
+                let count = vals.len().min(deque.len().saturating_sub(1));
+                let expected = (0..count)
+                    .map(|_| deque.pop_back().unwrap())
+                    .collect::<Vec<_>>();
+                let popped = store.list_pop_back_many(name, count as u64).unwrap();
+                if popped != expected {
+                    return false;
+                }
+            }
+        }
+    }
+
+    deep_eq(&store, name, &deque) && store.items.len() == deque.len() + 1
+}
+
+#[test]
+fn counted_pops_empty_the_list() {
+    let name = b"drain";
+    let store = TempDb::new();
+
+    let vals = (0..10u8).map(|i| sled::IVec::from(vec![i]));
+    assert_eq!(store.list_push_back_many(name, vals).unwrap(), 10);
+
+    assert_eq!(store.list_pop_back_many(name, 3).unwrap().len(), 3);
+    let rest = store.list_pop_front_many(name, 100).unwrap();
+    assert_eq!(
+        rest,
+        (0..7u8)
+            .map(|i| sled::IVec::from(vec![i]))
+            .collect::<Vec<_>>()
+    );
+    assert!(store.list_pop_front_many(name, 1).unwrap().is_empty());
+    assert_eq!(store.items.len(), 0);
+}
+
+#[test]
+fn remove_moves_only_elements_near_the_match() {
+    let name = b"l";
+    let store = TempDb::new();
+
+    let vals = (0..100u8).map(|i| sled::IVec::from(vec![i]));
+    store.list_push_back_many(name, vals).unwrap();
+
+    let keys = |store: &TempDb| {
+        store
+            .items
+            .iter()
+            .keys()
+            .collect::<Result<std::collections::HashSet<_>, _>>()
+            .unwrap()
+    };
+
+    for (value, count) in [(1u8, 1i64), (98, -1), (97, 1)].iter() {
+        let before = keys(&store);
+        assert_eq!(store.list_remove(name, *count, &[*value]).unwrap(), 1);
+        let after = keys(&store);
+
+        // the removed element's key goes, and at most a couple of survivors move
+        assert!(before.difference(&after).count() <= 3);
+    }
+
+    let expected = (0..100u8)
+        .filter(|i| ![1, 97, 98].contains(i))
+        .map(|i| sled::IVec::from(vec![i]))
+        .collect::<Vec<_>>();
+    let found = store
+        .list_range(name, 0, -1)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn repeated_inserts_into_a_full_capped_list_rebalance() {
+    let name = b"capped";
+    let store = TempDb::new();
+    let cap = std::num::NonZeroU64::new(3).unwrap();
+
+    for i in 0..3u8 {
+        store
+            .list_push_back_capped(name, vec![i].into(), cap)
+            .unwrap();
+    }
+
+    // each insert evicts the head, and halves the gap it inserts into until it runs out
+    let mut expected = VecDeque::from(vec![vec![0], vec![1], vec![2]]);
+    for i in 3..100u8 {
+        assert_eq!(
+            store.list_insert_at(name, 2, vec![i].into()).unwrap(),
+            Some(3)
+        );
+        expected.insert(2, vec![i]);
+        expected.pop_front();
+    }
+
+    assert!(deep_eq(&store, name, &expected));
+    assert_eq!(store.items.len(), 4);
+}
+
+#[quickcheck]
+fn trimming_a_gapped_list_makes_it_regular(
+    (vals, inserts, start, stop): (u8, Vec<(u8, u8)>, i8, i8),
+) -> bool {
+    let name = b"gapped";
+    let store = TempDb::new();
+
+    let mut deque = (0..vals % 16 + 1).map(|v| vec![v]).collect::<VecDeque<_>>();
+    for val in &deque {
+        store.list_push_back(name, val.as_slice().into()).unwrap();
+    }
+
+    for (pos, val) in inserts {
+        let pos = pos as usize % (deque.len() + 1);
+        deque.insert(pos, vec![val]);
+        store
+            .list_insert_at(name, pos as u64, vec![val].into())
+            .unwrap();
+    }
+
+    store.list_trim(name, start as i64, stop as i64).unwrap();
+
+    let len = deque.len() as i64;
+    let start = if start < 0 {
+        len + start as i64
+    } else {
+        start as i64
+    }
+    .max(0);
+    let stop = if stop < 0 {
+        len + stop as i64
+    } else {
+        stop as i64
+    }
+    .min(len - 1);
+    let deque = if start > stop {
+        VecDeque::new()
+    } else {
+        deque
+            .into_iter()
+            .skip(start as usize)
+            .take((stop - start + 1) as usize)
+            .collect()
+    };
+
+    store.list_get_meta(name).unwrap().is_regular()
+        && store.list_len(name).unwrap() == deque.len() as u64
+        && deep_eq(&store, name, &deque)
+        && store.items.len() == deque.len() + !deque.is_empty() as usize
+}
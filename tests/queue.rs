@@ -0,0 +1,154 @@
+use std::{thread, time::Duration};
+
+mod common;
+use common::TempDb;
+
+#[test]
+fn acked_items_are_gone() {
+    let store = TempDb::new();
+    let queue = store.queue(b"pending", b"processing").unwrap();
+
+    queue.enqueue(b"a".into()).unwrap();
+    queue.enqueue(b"b".into()).unwrap();
+
+    let lease = queue.dequeue(Duration::from_secs(60)).unwrap().unwrap();
+    assert_eq!(lease.item, b"a");
+    assert_eq!(queue.len().unwrap(), 1);
+    assert_eq!(queue.in_flight().unwrap(), 1);
+
+    assert!(queue.ack(lease.id).unwrap());
+    assert!(!queue.ack(lease.id).unwrap());
+    assert_eq!(queue.in_flight().unwrap(), 0);
+
+    let lease = queue.dequeue(Duration::from_secs(60)).unwrap().unwrap();
+    assert_eq!(lease.item, b"b");
+    assert!(queue.dequeue(Duration::from_secs(60)).unwrap().is_none());
+
+    assert!(queue.ack(lease.id).unwrap());
+    assert_eq!(store.items.len(), 0);
+}
+
+#[test]
+fn expired_leases_go_back_to_the_front() {
+    let store = TempDb::new();
+    let queue = store.queue(b"pending", b"processing").unwrap();
+
+    for item in [b"a", b"b", b"c"] {
+        queue.enqueue(item.into()).unwrap();
+    }
+
+    let first = queue.dequeue(Duration::from_millis(20)).unwrap().unwrap();
+    let second = queue.dequeue(Duration::from_millis(20)).unwrap().unwrap();
+    let kept = queue.dequeue(Duration::from_secs(60)).unwrap().unwrap();
+    assert!(queue.is_empty().unwrap());
+
+    thread::sleep(Duration::from_millis(40));
+
+    // redelivered oldest first, under new lease ids
+    let again = queue.dequeue(Duration::from_secs(60)).unwrap().unwrap();
+    assert_eq!(again.item, first.item);
+    assert_ne!(again.id, first.id);
+    assert!(!queue.ack(first.id).unwrap());
+
+    assert_eq!(queue.len().unwrap(), 1);
+    assert_eq!(queue.in_flight().unwrap(), 2);
+    assert_eq!(queue.requeue_expired().unwrap(), 0);
+
+    let last = queue.dequeue(Duration::from_secs(60)).unwrap().unwrap();
+    assert_eq!(last.item, second.item);
+
+    for lease in [again, last, kept] {
+        assert!(queue.ack(lease.id).unwrap());
+    }
+    assert_eq!(store.items.len(), 0);
+}
+
+#[test]
+fn requeued_items_wake_blocked_consumers() {
+    let store = TempDb::new();
+    let queue = store.queue(b"pending", b"processing").unwrap();
+
+    queue.enqueue(b"job".into()).unwrap();
+    queue.dequeue(Duration::from_millis(10)).unwrap().unwrap();
+
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(queue.requeue_expired().unwrap(), 1);
+
+    let (name, item) = store
+        .list_pop_front_blocking(&[b"pending"], Some(Duration::from_secs(10)))
+        .unwrap()
+        .unwrap();
+    assert_eq!(name, b"pending");
+    assert_eq!(item, b"job");
+}
+
+#[test]
+fn requeueing_never_drops_items_from_a_capped_queue() {
+    let store = TempDb::new();
+    let queue = store.queue(b"pending", b"processing").unwrap();
+    let cap = std::num::NonZeroU64::new(2).unwrap();
+
+    for item in [b"a", b"b", b"c"] {
+        store
+            .list_push_back_capped(b"pending", item.as_ref().into(), cap)
+            .unwrap();
+    }
+    assert_eq!(queue.len().unwrap(), 2);
+
+    queue.dequeue(Duration::from_millis(20)).unwrap().unwrap();
+    queue.enqueue(b"d".into()).unwrap();
+    queue.enqueue(b"e".into()).unwrap();
+    assert_eq!(queue.len().unwrap(), 2);
+
+    thread::sleep(Duration::from_millis(40));
+
+    // the expired "b" goes back to the front without evicting anything
+    assert_eq!(queue.requeue_expired().unwrap(), 1);
+    assert_eq!(queue.len().unwrap(), 3);
+    assert_eq!(queue.in_flight().unwrap(), 0);
+    assert_eq!(store.list_get(b"pending", 0).unwrap().unwrap(), b"b");
+    assert_eq!(store.list_get(b"pending", 2).unwrap().unwrap(), b"e");
+
+    // the next push evicts the queue back down to its cap
+    queue.enqueue(b"f".into()).unwrap();
+    assert_eq!(queue.len().unwrap(), 2);
+    assert_eq!(store.list_get(b"pending", 0).unwrap().unwrap(), b"e");
+}
+
+#[test]
+fn leases_expire_by_deadline_not_dequeue_order() {
+    let store = TempDb::new();
+    let queue = store.queue(b"pending", b"processing").unwrap();
+
+    for item in [b"a", b"b", b"c"] {
+        queue.enqueue(item.into()).unwrap();
+    }
+
+    let forever = queue.dequeue(Duration::MAX).unwrap().unwrap();
+    let long = queue.dequeue(Duration::from_secs(60)).unwrap().unwrap();
+    let short = queue.dequeue(Duration::from_millis(20)).unwrap().unwrap();
+    assert!(short.id < long.id && long.id < forever.id);
+
+    thread::sleep(Duration::from_millis(40));
+
+    assert_eq!(queue.requeue_expired().unwrap(), 1);
+    assert_eq!(queue.in_flight().unwrap(), 2);
+    assert_eq!(
+        queue
+            .dequeue(Duration::from_secs(60))
+            .unwrap()
+            .unwrap()
+            .item,
+        b"c"
+    );
+    assert!(queue.ack(forever.id).unwrap());
+}
+
+#[test]
+fn items_and_leases_need_different_names() {
+    let store = TempDb::new();
+    assert!(matches!(
+        store.queue(b"jobs", b"jobs"),
+        Err(sledis::Error::Queue(sledis::queue::QueueError::SameName(_)))
+    ));
+}
@@ -185,3 +185,467 @@ mod one_table {
         model.validate()
     }
 }
+
+mod ordered {
+    use super::*;
+    use sledis::escaping::{escape_optimistic, TERMINATOR};
+    use std::ops::Bound;
+
+    const NAME: &[u8] = b"KEY";
+    // tables whose keys sort right next to `NAME`'s fields
+    const NEIGHBOURS: [&[u8]; 3] = [b"KE", b"KEYS", b"KEY\0"];
+
+    // the stored suffix of `field`, which decides the order fields are returned in
+    fn escaped(field: &[u8]) -> Vec<u8> {
+        let mut out = escape_optimistic(field).as_ref().to_vec();
+        out.extend_from_slice(&TERMINATOR);
+        out
+    }
+
+    // the fields of `model` in stored key order
+    fn sorted(model: &BTreeMap<Vec<u8>, Vec<u8>>) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut out = model
+            .iter()
+            .map(|(f, v)| (f.clone(), v.clone()))
+            .collect::<Vec<_>>();
+        out.sort_by_key(|(f, _)| escaped(f));
+        out
+    }
+
+    fn fill(fields: Vec<(Vec<u8>, Vec<u8>)>) -> (TempDb, BTreeMap<Vec<u8>, Vec<u8>>) {
+        let store = TempDb::new();
+        let mut model = BTreeMap::new();
+
+        for (field, val) in fields {
+            store
+                .table_insert(NAME, &field, val.as_slice().into())
+                .expect("failed to insert");
+            for name in NEIGHBOURS.iter() {
+                store
+                    .table_insert(name, &field, b"neighbour".as_ref().into())
+                    .expect("failed to insert");
+            }
+            model.insert(field, val);
+        }
+
+        (store, model)
+    }
+
+    fn collect<I>(iter: I) -> Vec<(Vec<u8>, Vec<u8>)>
+    where
+        I: Iterator<Item = Result<(Vec<u8>, sled::IVec), sledis::Error>>,
+    {
+        iter.map(|entry| {
+            let (field, val) = entry.expect("failed to read");
+            (field, val.to_vec())
+        })
+        .collect()
+    }
+
+    #[quickcheck]
+    fn iter_matches_model(fields: Vec<(Vec<u8>, Vec<u8>)>) -> bool {
+        let (store, model) = fill(fields);
+        let expected = sorted(&model);
+
+        let forward = collect(store.table_iter(NAME).expect("failed to iter"));
+        let mut backward = collect(store.table_iter(NAME).expect("failed to iter").rev());
+        backward.reverse();
+
+        forward == expected && backward == expected
+    }
+
+    #[quickcheck]
+    fn range_matches_model(
+        fields: Vec<(Vec<u8>, Vec<u8>)>,
+        start: Vec<u8>,
+        end: Vec<u8>,
+        inclusive: bool,
+    ) -> bool {
+        let (store, model) = fill(fields);
+        let (lo, hi) = (escaped(&start), escaped(&end));
+
+        let expected = sorted(&model)
+            .into_iter()
+            .filter(|(f, _)| {
+                let f = escaped(f);
+                f >= lo && if inclusive { f <= hi } else { f < hi }
+            })
+            .collect::<Vec<_>>();
+
+        let end = if inclusive {
+            Bound::Included(end)
+        } else {
+            Bound::Excluded(end)
+        };
+        let found = collect(
+            store
+                .table_range(NAME, (Bound::Included(start), end))
+                .expect("failed to range"),
+        );
+
+        found == expected
+    }
+
+    #[quickcheck]
+    fn scan_pages_through_table(fields: Vec<(Vec<u8>, Vec<u8>)>, count: u8) -> bool {
+        let (store, model) = fill(fields);
+        let count = count as usize % 5 + 1;
+
+        let mut found = Vec::new();
+        let mut cursor = None;
+
+        loop {
+            let page = store
+                .table_scan(NAME, cursor.as_deref(), count)
+                .expect("failed to scan");
+            assert!(page.fields.len() <= count);
+
+            found.extend(page.fields.into_iter().map(|(f, v)| (f, v.to_vec())));
+            cursor = match page.cursor {
+                Some(cursor) => Some(cursor),
+                None => break,
+            };
+        }
+
+        found == sorted(&model)
+    }
+
+    #[test]
+    fn scan_rejects_zero_count() {
+        use sledis::{table::TableError, Error};
+
+        let (store, _) = fill(vec![(b"a".to_vec(), b"1".to_vec())]);
+
+        assert!(matches!(
+            store.table_scan(NAME, None, 0),
+            Err(Error::Table(TableError::ZeroCount))
+        ));
+    }
+
+    // maps bytes onto the ones escaping treats specially so prefixes often match
+    fn narrow(bytes: Vec<u8>) -> Vec<u8> {
+        bytes
+            .into_iter()
+            .map(|b| [0, 1, 255, b'a'][b as usize % 4])
+            .collect()
+    }
+
+    #[quickcheck]
+    fn prefix_scan_matches_model(fields: Vec<(Vec<u8>, Vec<u8>)>, prefix: Vec<u8>) -> bool {
+        let fields = fields.into_iter().map(|(f, v)| (narrow(f), v)).collect();
+        let prefix = narrow(prefix);
+        let (store, model) = fill(fields);
+
+        let expected = sorted(&model)
+            .into_iter()
+            .filter(|(f, _)| f.starts_with(&prefix))
+            .collect::<Vec<_>>();
+        let found = collect(
+            store
+                .table_scan_prefix(NAME, &prefix)
+                .expect("failed to scan"),
+        );
+
+        found == expected
+    }
+
+    #[test]
+    fn iteration_skips_expired_fields() {
+        let store = TempDb::new();
+
+        for field in [b"a", b"b", b"c"].iter() {
+            store
+                .table_insert(NAME, *field, b"val".as_ref().into())
+                .expect("failed to insert");
+        }
+        store
+            .table_expire(
+                NAME,
+                b"b",
+                std::time::SystemTime::now() + std::time::Duration::from_millis(50),
+            )
+            .expect("failed to expire");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let fields = collect(store.table_iter(NAME).expect("failed to iter"))
+            .into_iter()
+            .map(|(f, _)| f)
+            .collect::<Vec<_>>();
+        assert_eq!(fields, vec![b"a".to_vec(), b"c".to_vec()]);
+
+        store.remove_item(NAME).expect("failed to remove");
+        assert!(store
+            .table_iter(NAME)
+            .expect("failed to iter")
+            .next()
+            .is_none());
+    }
+}
+
+mod many {
+    use super::*;
+
+    const NAME: &[u8] = b"KEY";
+
+    #[derive(Debug, Clone)]
+    enum ManyOp {
+        Insert(Vec<(Vec<u8>, Vec<u8>)>),
+        Remove(Vec<Vec<u8>>),
+        Get(Vec<Vec<u8>>),
+    }
+
+    impl Arbitrary for ManyOp {
+        fn arbitrary<G: Gen>(gen: &mut G) -> Self {
+            match u8::arbitrary(gen) % 3 {
+                0 => ManyOp::Insert(Vec::arbitrary(gen)),
+                1 => ManyOp::Remove(Vec::arbitrary(gen)),
+                2 => ManyOp::Get(Vec::arbitrary(gen)),
+                _ => unreachable!(),
+            }
+        }
+
+        fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+            match self {
+                ManyOp::Insert(entries) => Box::new(entries.shrink().map(ManyOp::Insert)),
+                ManyOp::Remove(keys) => Box::new(keys.shrink().map(ManyOp::Remove)),
+                ManyOp::Get(keys) => Box::new(keys.shrink().map(ManyOp::Get)),
+            }
+        }
+    }
+
+    impl Models {
+        fn apply_many_op(&mut self, op: ManyOp) {
+            let model = self.model.entry(NAME.to_vec()).or_default();
+
+            match op {
+                ManyOp::Insert(entries) => {
+                    let added = self
+                        .store
+                        .table_insert_many(
+                            NAME,
+                            entries
+                                .iter()
+                                .map(|(k, v)| (k.as_slice(), v.as_slice().into())),
+                        )
+                        .expect("failed to insert");
+
+                    let before = model.len();
+                    model.extend(entries);
+                    assert_eq!(added, (model.len() - before) as u64);
+                }
+                ManyOp::Remove(keys) => {
+                    let removed = self
+                        .store
+                        .table_remove_many(NAME, &keys)
+                        .expect("failed to remove");
+
+                    let before = model.len();
+                    for key in &keys {
+                        model.remove(key);
+                    }
+                    assert_eq!(removed, (before - model.len()) as u64);
+                }
+                ManyOp::Get(keys) => {
+                    let found = self
+                        .store
+                        .table_get_many(NAME, &keys)
+                        .expect("failed to get");
+
+                    assert_eq!(found.len(), keys.len());
+                    for (key, val) in keys.iter().zip(found) {
+                        assert_eq!(
+                            val.as_ref().map(AsRef::as_ref),
+                            model.get(key).map(Vec::as_slice)
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[quickcheck]
+    fn always_equiv(ops: Vec<ManyOp>) -> bool {
+        let mut model = Models::new();
+        for op in ops {
+            model.apply_many_op(op);
+        }
+        model.validate()
+    }
+}
+
+mod numeric {
+    use super::*;
+    use sledis::{table::TableError, Error};
+
+    const NAME: &[u8] = b"KEY";
+
+    #[quickcheck]
+    fn incr_matches_model(ops: Vec<(u8, i64)>) -> bool {
+        let store = TempDb::new();
+        let mut model = BTreeMap::new();
+
+        for (field, delta) in ops {
+            let field = [field % 4];
+            let current = *model.get(&field).unwrap_or(&0i64);
+
+            match (
+                store.table_incr_by(NAME, &field, delta),
+                current.checked_add(delta),
+            ) {
+                (Ok(new), Some(expected)) if new == expected => {
+                    model.insert(field, new);
+                }
+                (Err(Error::Table(TableError::Overflow)), None) => {}
+                _ => return false,
+            }
+        }
+
+        model.iter().all(|(field, val)| {
+            store.table_get(NAME, field).expect("failed to get")
+                == Some(val.to_string().as_bytes().into())
+        })
+    }
+
+    #[test]
+    fn incr_overflow_leaves_value() {
+        let store = TempDb::new();
+
+        assert_eq!(store.table_incr_by(NAME, b"i", i64::MAX).unwrap(), i64::MAX);
+        assert!(matches!(
+            store.table_incr_by(NAME, b"i", 1),
+            Err(Error::Table(TableError::Overflow))
+        ));
+        assert_eq!(store.table_incr_by(NAME, b"i", i64::MIN).unwrap(), -1);
+    }
+
+    #[test]
+    fn incr_by_float_accumulates() {
+        let store = TempDb::new();
+
+        assert_eq!(store.table_incr_by_float(NAME, b"f", 1.5).unwrap(), 1.5);
+        assert_eq!(store.table_incr_by_float(NAME, b"f", -0.25).unwrap(), 1.25);
+        assert_eq!(
+            store.table_get(NAME, b"f").unwrap(),
+            Some(b"1.25".as_ref().into())
+        );
+
+        // integers are valid floats
+        store.table_incr_by(NAME, b"i", 3).unwrap();
+        assert_eq!(store.table_incr_by_float(NAME, b"i", 0.5).unwrap(), 3.5);
+
+        assert!(matches!(
+            store.table_incr_by_float(NAME, b"f", f64::INFINITY),
+            Err(Error::Table(TableError::Overflow))
+        ));
+        assert_eq!(store.table_get_meta(NAME).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn incr_rejects_non_numbers() {
+        let store = TempDb::new();
+
+        store
+            .table_insert(NAME, b"text", b"abc".as_ref().into())
+            .unwrap();
+        store
+            .table_insert(NAME, b"float", b"1.5".as_ref().into())
+            .unwrap();
+
+        assert!(matches!(
+            store.table_incr_by(NAME, b"text", 1),
+            Err(Error::Table(TableError::NotAnInteger(_)))
+        ));
+        assert!(matches!(
+            store.table_incr_by(NAME, b"float", 1),
+            Err(Error::Table(TableError::NotAnInteger(_)))
+        ));
+        assert!(matches!(
+            store.table_incr_by_float(NAME, b"text", 1.0),
+            Err(Error::Table(TableError::NotAFloat(_)))
+        ));
+
+        assert_eq!(
+            store.table_get(NAME, b"text").unwrap(),
+            Some(b"abc".as_ref().into())
+        );
+        assert_eq!(store.table_get_meta(NAME).unwrap().len(), 2);
+    }
+}
+
+mod conditional {
+    use super::*;
+
+    const NAME: &[u8] = b"KEY";
+
+    #[derive(Debug, Clone)]
+    enum CondOp {
+        InsertIfAbsent(u8, Vec<u8>),
+        CompareAndSwap(u8, Option<Vec<u8>>, Option<Vec<u8>>),
+    }
+
+    impl Arbitrary for CondOp {
+        fn arbitrary<G: Gen>(gen: &mut G) -> Self {
+            // few fields and short values, so comparisons often match
+            let val = |gen: &mut G| vec![u8::arbitrary(gen) % 2];
+            let field = u8::arbitrary(gen) % 4;
+
+            match u8::arbitrary(gen) % 2 {
+                0 => CondOp::InsertIfAbsent(field, val(gen)),
+                1 => {
+                    let expected = Some(val(gen)).filter(|_| bool::arbitrary(gen));
+                    let new = Some(val(gen)).filter(|_| bool::arbitrary(gen));
+                    CondOp::CompareAndSwap(field, expected, new)
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    impl Models {
+        fn apply_cond_op(&mut self, op: CondOp) {
+            let model = self.model.entry(NAME.to_vec()).or_default();
+
+            match op {
+                CondOp::InsertIfAbsent(field, val) => {
+                    let set = self
+                        .store
+                        .table_insert_if_absent(NAME, &[field], val.as_slice().into())
+                        .expect("failed to insert");
+
+                    assert_eq!(set, !model.contains_key(&vec![field]));
+                    model.entry(vec![field]).or_insert(val);
+                }
+                CondOp::CompareAndSwap(field, expected, new) => {
+                    let swapped = self
+                        .store
+                        .table_compare_and_swap(
+                            NAME,
+                            &[field],
+                            expected.as_deref(),
+                            new.as_deref().map(Into::into),
+                        )
+                        .expect("failed to swap");
+
+                    let matches = model.get(&vec![field]) == expected.as_ref();
+                    assert_eq!(swapped, matches);
+
+                    if matches {
+                        match new {
+                            Some(new) => model.insert(vec![field], new),
+                            None => model.remove(&vec![field]),
+                        };
+                    }
+                }
+            }
+        }
+    }
+
+    #[quickcheck]
+    fn always_equiv(ops: Vec<CondOp>) -> bool {
+        let mut model = Models::new();
+        for op in ops {
+            model.apply_cond_op(op);
+        }
+        model.validate()
+    }
+}